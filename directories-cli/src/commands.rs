@@ -0,0 +1,445 @@
+//  COMMANDS.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:02:43
+//  Last edited:
+//    18 Oct 2026, 22:29:20
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the subcommands of the `directories` binary.
+// 
+
+use std::collections::HashSet;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::{self, DirEntry, ReadDir};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+use directories::{DirectoryExt as _, FieldSpec, LayoutLock, Problem};
+use directories::layout::{DynamicLayout, Entry};
+use directories::schema::EntryKind;
+
+
+/***** ERRORS *****/
+/// Defines errors that may occur when running the subcommands.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to lock a layout.
+    Lock { path: PathBuf, err: Box<directories::Error> },
+    /// Failed to create the missing paths of a layout.
+    Ensure { path: PathBuf, err: Box<directories::Error> },
+    /// Failed to read a directory.
+    DirRead { path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry within a directory.
+    DirEntryRead { path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to remove a file or directory.
+    Remove { path: PathBuf, err: std::io::Error },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Lock { path, .. }                => write!(f, "Failed to lock layout in '{}'", path.display()),
+            Ensure { path, .. }              => write!(f, "Failed to create layout in '{}'", path.display()),
+            DirRead { path, .. }             => write!(f, "Failed to read directory '{}'", path.display()),
+            DirEntryRead { path, entry, .. } => write!(f, "Failed to read entry {} in directory '{}'", entry, path.display()),
+            Remove { path, .. }              => write!(f, "Failed to remove '{}'", path.display()),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Lock { err, .. }         => Some(err),
+            Ensure { err, .. }       => Some(err),
+            DirRead { err, .. }      => Some(err),
+            DirEntryRead { err, .. } => Some(err),
+            Remove { err, .. }       => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the given path relative to the root, for printing.
+/// 
+/// # Arguments
+/// - `root`: The root directory of the layout.
+/// - `path`: The path to make relative.
+/// 
+/// # Returns
+/// A displayable string of the path relative to the root, or the path as-is if it's not in the root.
+fn rel(root: &Path, path: &Path) -> String {
+    match path.strip_prefix(root) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".into(),
+        Ok(rel)                               => rel.display().to_string(),
+        Err(_)                                => path.display().to_string(),
+    }
+}
+
+/// Acquires an exclusive lock on the given layout, if it declares a lock file.
+/// 
+/// Unlike [`DirectoryExt::lock()`](directories::DirectoryExt::lock()), a layout without a lock file is not an error here: the commands work on any specification, and most of them don't declare a lock file because nothing else uses them concurrently. Those are simply used without locking.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to lock.
+/// 
+/// # Returns
+/// The [`LayoutLock`] that holds the lock until it is dropped, or [`None`] if the layout declares no lock file.
+/// 
+/// # Errors
+/// This function errors if we failed to acquire the lock.
+fn lock(layout: &DynamicLayout) -> Result<Option<LayoutLock>, Error> {
+    let path: PathBuf = match layout.lockfile() {
+        Some(path) => path,
+        None       => { return Ok(None); },
+    };
+    debug!("Locking '{}'...", path.display());
+    layout.lock().map(Some).map_err(|err| Error::Lock { path: layout.path().into(), err: Box::new(err) })
+}
+
+/// Collects the paths of all dynamic directories in a layout.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to search.
+/// - `roots`: The list to add the paths of the dynamic directories to.
+fn find_dynamic(layout: &DynamicLayout, roots: &mut Vec<PathBuf>) {
+    let mut todo: Vec<&Entry> = layout.iter().map(|(_, entry)| entry).collect();
+    while let Some(entry) = todo.pop() {
+        match entry {
+            Entry::Path(_, _)             => {},
+            Entry::Layout(nested)         => todo.extend(nested.iter().map(|(_, entry)| entry)),
+            Entry::Optional(Some(nested)) => todo.push(nested),
+            Entry::Optional(None)         => {},
+            Entry::Dynamic(path, entries) => {
+                roots.push(path.clone());
+                todo.extend(entries.values());
+            },
+        }
+    }
+}
+
+/// Finds all entries in the root of a layout that are not described by the layout.
+/// 
+/// The lock file of the layout, if any, is never reported. Entries are expected if they are declared by the layout, if they are an ancestor of a declared path, or if they are nested in a declared directory that has no declared paths in it (whose contents are thus free-form).
+/// 
+/// Unexpected entries in a dynamic directory (e.g., a nested layout that is incomplete) are reported separately as invalid, since they are data that the layout does describe; they just don't match it.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to compare against.
+/// - `ignore`: A list of paths to never report (e.g., the specification file itself).
+/// 
+/// # Returns
+/// A tuple with the topmost unexpected paths outside of any dynamic directory, and the topmost unexpected paths inside of one, both sorted.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the directories in the root.
+fn find_unexpected(layout: &DynamicLayout, ignore: &[PathBuf]) -> Result<(Vec<PathBuf>, Vec<PathBuf>), Error> {
+    // Collect everything the layout declares, including the dynamic directories themselves
+    let mut declared: Vec<(PathBuf, EntryKind)> = vec![];
+    let res: Result<(), std::convert::Infallible> = layout.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| {
+        declared.push((path.into(), spec.kind));
+        Ok(())
+    });
+    if let Err(err) = res { match err {} }
+    let mut dynamic: Vec<PathBuf> = vec![];
+    find_dynamic(layout, &mut dynamic);
+    declared.extend(dynamic.iter().map(|p| (p.clone(), EntryKind::Dir)));
+
+    // Deduce the ancestors and the free-form directories (which dynamic directories never are, since we want to find invalid entries in them)
+    let mut ancestors: HashSet<PathBuf> = HashSet::new();
+    for (path, _) in &declared {
+        ancestors.extend(path.ancestors().skip(1).map(PathBuf::from));
+    }
+    let opaque: HashSet<&Path> = declared.iter().filter(|(p, k)| *k != EntryKind::File && !ancestors.contains(p) && !dynamic.contains(p)).map(|(p, _)| p.as_path()).collect();
+    let declared: HashSet<&Path> = declared.iter().map(|(p, _)| p.as_path()).collect();
+    let ignore: HashSet<PathBuf> = ignore.iter().cloned().chain(layout.lockfile()).filter_map(|p| fs::canonicalize(p).ok()).collect();
+
+    // Now recurse the directory to find anything that is neither of those
+    let mut unexpected: Vec<PathBuf> = vec![];
+    let mut invalid: Vec<PathBuf> = vec![];
+    let mut todo: Vec<PathBuf> = vec![ layout.path().into() ];
+    while let Some(dir) = todo.pop() {
+        debug!("Examining '{}'...", dir.display());
+        let entries: ReadDir = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::DirRead { path: dir, err }); },
+        };
+        for (i, entry) in entries.enumerate() {
+            let entry: DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err)  => { return Err(Error::DirEntryRead { path: dir, entry: i, err }); },
+            };
+            let path: PathBuf = entry.path();
+
+            // Skip ignored files
+            if fs::canonicalize(&path).map(|p| ignore.contains(&p)).unwrap_or(false) { continue; }
+
+            // See what to do with it
+            if opaque.contains(path.as_path()) {
+                continue;
+            } else if declared.contains(path.as_path()) || ancestors.contains(&path) {
+                if path.is_dir() { todo.push(path); }
+            } else if dynamic.iter().any(|root| path.starts_with(root)) {
+                invalid.push(path);
+            } else {
+                unexpected.push(path);
+            }
+        }
+    }
+
+    // Done
+    unexpected.sort();
+    invalid.sort();
+    Ok((unexpected, invalid))
+}
+
+/// Prints a report of the problems with a layout.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] that has been validated.
+/// - `problems`: The [`Problem`]s found.
+fn print_problems(layout: &DynamicLayout, problems: &[Problem]) {
+    println!("Layout of '{}' has {} problem{}:", layout.path().display(), problems.len(), if problems.len() == 1 { "" } else { "s" });
+    for problem in problems {
+        println!(" - {problem}");
+    }
+}
+
+/// Prints a nested layout as a tree.
+/// 
+/// # Arguments
+/// - `root`: The root directory of the layout.
+/// - `layout`: The (nested) [`DynamicLayout`] to print.
+/// - `indent`: The indentation of the layout's fields.
+fn print_layout(root: &Path, layout: &DynamicLayout, indent: usize) {
+    for (name, entry) in layout.iter() {
+        print_entry(root, name, entry, indent, "");
+    }
+}
+
+/// Prints a single entry in a layout as a tree.
+/// 
+/// # Arguments
+/// - `root`: The root directory of the layout.
+/// - `name`: The name of the entry.
+/// - `entry`: The [`Entry`] to print.
+/// - `indent`: The indentation of the entry.
+/// - `note`: Any additional note to print after the entry.
+fn print_entry(root: &Path, name: &str, entry: &Entry, indent: usize, note: &str) {
+    let prefix: String = "    ".repeat(indent);
+    let status: &str = if entry.exists() { "" } else { " [MISSING]" };
+    match entry {
+        Entry::Path(path, kind) => {
+            let suffix: &str = if *kind == EntryKind::Dir { "/" } else { "" };
+            println!("{prefix}{name}: {}{suffix}{note}{status}", rel(root, path));
+        },
+        Entry::Layout(nested) => {
+            println!("{prefix}{name}: {}/{note}{status}", rel(root, nested.path()));
+            print_layout(root, nested, indent + 1);
+        },
+        Entry::Optional(Some(nested)) => print_entry(root, name, nested, indent, " (optional)"),
+        Entry::Optional(None) => println!("{prefix}{name}: <not present> (optional)"),
+        Entry::Dynamic(_, entries) => {
+            println!("{prefix}{name}: {} entr{}{note}{status}", entries.len(), if entries.len() == 1 { "y" } else { "ies" });
+            for (path, nested) in entries {
+                let name: String = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string());
+                print_entry(root, &name, nested, indent + 1, "");
+            }
+        },
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Checks whether the given layout matches what is on disk.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to check.
+/// 
+/// # Returns
+/// Whether the layout matched.
+pub fn check(layout: &DynamicLayout) -> bool {
+    let problems: Vec<Problem> = layout.validate();
+    if problems.is_empty() {
+        println!("Layout of '{}' is OK", layout.path().display());
+        true
+    } else {
+        print_problems(layout, &problems);
+        false
+    }
+}
+
+
+
+/// Creates all mandatory files and directories of the given layout that do not exist yet.
+/// 
+/// If the layout declares a lock file, it is locked while creating it.
+/// 
+/// Paths that are declared neither a file nor a directory are skipped.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to create.
+/// - `dry_run`: If true, only prints what would be created.
+/// 
+/// # Errors
+/// This function errors if we failed to create any of the paths.
+pub fn create(layout: &DynamicLayout, dry_run: bool) -> Result<(), Error> {
+    // Make sure we're the only ones changing the layout
+    let _lock: Option<LayoutLock> = if dry_run { None } else { lock(layout)? };
+
+    // Report what we will create
+    let root: &Path = layout.path();
+    let res: Result<(), std::convert::Infallible> = layout.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| {
+        if path.exists() { return Ok(()); }
+        match spec.kind {
+            EntryKind::Dir  => println!("Creating directory '{}'", rel(root, path)),
+            EntryKind::File => println!("Creating file '{}'", rel(root, path)),
+            EntryKind::Any  => warn!("Not creating '{}' because it is declared as neither a file nor a directory", rel(root, path)),
+        }
+        Ok(())
+    });
+    if let Err(err) = res { match err {} }
+
+    // Then create it
+    if dry_run { return Ok(()); }
+    layout.ensure().map_err(|err| Error::Ensure { path: root.into(), err: Box::new(err) })
+}
+
+
+
+/// Prints the given layout as a tree, with the paths it resolves to.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to print.
+pub fn tree(layout: &DynamicLayout) {
+    println!("{}/", layout.path().display());
+    print_layout(layout.path(), layout, 1);
+}
+
+
+
+/// Prints the differences between the given layout and what is on disk, i.e., which paths are missing, which are not described by the layout and which are invalid entries of dynamic directories.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to compare.
+/// - `ignore`: A list of paths to never report as unexpected (e.g., the specification file itself).
+/// 
+/// # Returns
+/// Whether there were no differences.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the directories in the layout's root.
+pub fn diff(layout: &DynamicLayout, ignore: &[PathBuf]) -> Result<bool, Error> {
+    let root: &Path = layout.path();
+    let problems: Vec<Problem> = layout.validate();
+    let (unexpected, invalid): (Vec<PathBuf>, Vec<PathBuf>) = find_unexpected(layout, ignore)?;
+    for problem in &problems {
+        println!("- {} ({problem})", rel(root, problem.path()));
+    }
+    for path in &unexpected {
+        println!("+ {}{}", rel(root, path), if path.is_dir() { "/" } else { "" });
+    }
+    for path in &invalid {
+        println!("! {}{} (invalid entry of a dynamic directory)", rel(root, path), if path.is_dir() { "/" } else { "" });
+    }
+    Ok(problems.is_empty() && unexpected.is_empty() && invalid.is_empty())
+}
+
+
+
+/// Removes everything in the root of the given layout that is not described by it.
+/// 
+/// Nothing in a dynamic directory is ever removed, since it is data described by the layout. Entries in it that don't match the layout are reported as invalid instead.
+/// 
+/// If the layout declares a lock file, it is locked while cleaning it.
+/// 
+/// # Arguments
+/// - `layout`: The [`DynamicLayout`] to clean.
+/// - `ignore`: A list of paths to never remove (e.g., the specification file itself).
+/// - `dry_run`: If true, only prints what would be removed.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the directories or remove any of the paths.
+pub fn clean(layout: &DynamicLayout, ignore: &[PathBuf], dry_run: bool) -> Result<(), Error> {
+    // Make sure we're the only ones changing the layout
+    let _lock: Option<LayoutLock> = if dry_run { None } else { lock(layout)? };
+
+    let root: &Path = layout.path();
+    let (unexpected, invalid): (Vec<PathBuf>, Vec<PathBuf>) = find_unexpected(layout, ignore)?;
+    for path in invalid {
+        warn!("Not removing invalid entry '{}' of a dynamic directory; fix or remove it manually", rel(root, &path));
+    }
+    for path in unexpected {
+        if dry_run {
+            println!("Would remove '{}'", rel(root, &path));
+            continue;
+        }
+        println!("Removing '{}'", rel(root, &path));
+        // Note: we don't follow symlinks, to only remove the links themselves
+        let is_dir: bool = fs::symlink_metadata(&path).map(|md| md.is_dir()).unwrap_or(false);
+        let res: std::io::Result<()> = if is_dir { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        if let Err(err) = res { return Err(Error::Remove { path, err }); }
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use directories::schema::{EntrySchema, FieldSchema, LayoutSchema};
+
+    use super::*;
+
+    #[test]
+    fn test_find_unexpected() {
+        let root: PathBuf = std::env::temp_dir().join(format!("directories-cli-test-unexpected-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["data/free/form", "plugins/good", "plugins/bad", "stray"] { fs::create_dir_all(root.join(dir)).unwrap(); }
+        for file in ["config.toml", "data/free/form/file", "plugins/good/manifest.toml", "plugins/bad/other.txt", "junk.txt", "spec.toml"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        // A file, a free-form directory and a dynamic directory of which every entry needs a manifest
+        let plugin: LayoutSchema = LayoutSchema {
+            fields: vec![FieldSchema::new("manifest", Some("manifest.toml".into()), EntrySchema { kind: EntryKind::File, ..Default::default() })],
+            ..Default::default()
+        };
+        let schema: LayoutSchema = LayoutSchema {
+            fields: vec![
+                FieldSchema::new("config", Some("config.toml".into()), EntrySchema { kind: EntryKind::File, ..Default::default() }),
+                FieldSchema::new("data", Some("data".into()), EntrySchema { kind: EntryKind::Dir, ..Default::default() }),
+                FieldSchema::new("plugins", Some("plugins".into()), EntrySchema {
+                    kind: EntryKind::Dir,
+                    dynamic: true,
+                    nested: Some(plugin),
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let layout: DynamicLayout = DynamicLayout::try_from_schema(&schema, &root).unwrap();
+
+        // Only the junk is unexpected, while the incomplete plugin is reported as invalid instead
+        let (unexpected, invalid): (Vec<PathBuf>, Vec<PathBuf>) = find_unexpected(&layout, &[root.join("spec.toml")]).unwrap();
+        assert_eq!(unexpected, vec![root.join("junk.txt"), root.join("stray")]);
+        assert_eq!(invalid, vec![root.join("plugins/bad")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
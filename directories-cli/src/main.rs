@@ -0,0 +1,158 @@
+//  MAIN.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:02:43
+//  Last edited:
+//    18 Oct 2026, 22:02:43
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Entrypoint to the `directories` binary, which validates and
+//!   scaffolds layouts given by specification files.
+// 
+
+// Declare the submodules
+mod commands;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use humanlog::{DebugMode, HumanLogger};
+use log::error;
+
+use directories::layout::DynamicLayout;
+use directories::schema::LayoutSchema;
+
+
+/***** ARGUMENTS *****/
+/// Defines the arguments for the `directories` binary.
+#[derive(Parser)]
+#[clap(about = "Validates and scaffolds directory layouts described by TOML/YAML specification files.")]
+struct Arguments {
+    /// Whether to enable full debug structures or not
+    #[clap(long, global=true, help="If given, enables additional logging statements.")]
+    debug : bool,
+
+    /// The subcommand to run.
+    #[clap(subcommand)]
+    action : Action,
+}
+
+/// Defines the subcommands of the `directories` binary.
+#[derive(Subcommand)]
+enum Action {
+    /// Checks whether a directory matches a layout.
+    #[clap(about = "Checks whether all mandatory files and directories of a layout exist. Exits with 1 if they don't.")]
+    Check(LayoutArguments),
+    /// Creates any missing paths of a layout.
+    #[clap(about = "Creates all mandatory files and directories of a layout that do not exist yet.")]
+    Create {
+        #[clap(flatten)]
+        layout  : LayoutArguments,
+        /// Whether to only show what would be created.
+        #[clap(long, help="If given, only prints what would be created without creating it.")]
+        dry_run : bool,
+    },
+    /// Shows a layout as a tree.
+    #[clap(about = "Shows a layout as a tree, with the paths it resolves to in the given root.")]
+    Tree(LayoutArguments),
+    /// Shows how a directory differs from a layout.
+    #[clap(about = "Shows which paths of a layout are missing and which paths are not described by it. Exits with 1 if there are any.")]
+    Diff(LayoutArguments),
+    /// Removes anything not in a layout.
+    #[clap(about = "Removes all files and directories that are not described by a layout.")]
+    Clean {
+        #[clap(flatten)]
+        layout  : LayoutArguments,
+        /// Whether to only show what would be removed.
+        #[clap(long, help="If given, only prints what would be removed without removing it.")]
+        dry_run : bool,
+    },
+}
+
+/// Defines the arguments shared by all subcommands.
+#[derive(clap::Args)]
+struct LayoutArguments {
+    /// The specification of the layout.
+    #[clap(name="SPEC", help="The layout specification file to use (a '.toml', '.yaml' or '.yml' file).")]
+    spec : PathBuf,
+    /// The root directory of the layout.
+    #[clap(name="ROOT", default_value="./", help="The root directory to which the layout applies.")]
+    root : PathBuf,
+}
+impl LayoutArguments {
+    /// Loads the layout specification and initializes it in the root directory.
+    /// 
+    /// # Returns
+    /// A new [`DynamicLayout`].
+    /// 
+    /// Exits the process if we failed to load the specification or initialize the layout.
+    fn load(&self) -> DynamicLayout {
+        let schema: LayoutSchema = match LayoutSchema::from_path(&self.spec) {
+            Ok(schema) => schema,
+            Err(err)   => { error!("{}", trace(&err)); std::process::exit(2); },
+        };
+        match DynamicLayout::try_from_schema(&schema, &self.root) {
+            Ok(layout) => layout,
+            Err(err)   => { error!("{}", trace(&err)); std::process::exit(2); },
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Formats an error together with all of its sources.
+/// 
+/// # Arguments
+/// - `err`: The error to format.
+/// 
+/// # Returns
+/// A [`String`] with the error and its sources, separated by colons.
+fn trace(err: &dyn std::error::Error) -> String {
+    let mut res: String = err.to_string();
+    let mut source: Option<&dyn std::error::Error> = err.source();
+    while let Some(err) = source {
+        res.push_str(&format!(": {err}"));
+        source = err.source();
+    }
+    res
+}
+
+
+
+
+
+/***** ENTRYPOINT *****/
+fn main() {
+    // Parse the CLI
+    let args: Arguments = Arguments::parse();
+
+    // Initialize the logger
+    if let Err(err) = HumanLogger::terminal(if args.debug { DebugMode::Debug } else { DebugMode::HumanFriendly }).init() {
+        eprintln!("WARNING: Failed to initialize logger: {err} (no logging enabled for this session)");
+    }
+
+    // Run the subcommand
+    let ok: bool = match args.action {
+        Action::Check(largs) => commands::check(&largs.load()),
+        Action::Create { layout: largs, dry_run } => match commands::create(&largs.load(), dry_run) {
+            Ok(())   => true,
+            Err(err) => { error!("{}", trace(&err)); std::process::exit(2); },
+        },
+        Action::Tree(largs) => { commands::tree(&largs.load()); true },
+        Action::Diff(largs) => match commands::diff(&largs.load(), std::slice::from_ref(&largs.spec)) {
+            Ok(ok)   => ok,
+            Err(err) => { error!("{}", trace(&err)); std::process::exit(2); },
+        },
+        Action::Clean { layout: largs, dry_run } => match commands::clean(&largs.load(), std::slice::from_ref(&largs.spec), dry_run) {
+            Ok(())   => true,
+            Err(err) => { error!("{}", trace(&err)); std::process::exit(2); },
+        },
+    };
+    if !ok { std::process::exit(1); }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    23 Apr 2023, 10:45:48
 //  Last edited:
-//    25 Jun 2023, 12:04:01
+//    18 Oct 2026, 22:42:59
 //  Auto updated?
 //    Yes
 // 
@@ -13,14 +13,17 @@
 // 
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 
 use enum_debug::EnumDebug;
 use proc_macro::TokenStream;
 use proc_macro_error::{Diagnostic, Level};
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
 use syn::{Attribute, Data, DataStruct, Expr, Generics, Ident, Lit, Meta, Token, Type, Visibility};
-use syn::__private::Span;
+use syn::__private::{Span, TokenStream2};
 use syn::parse::ParseBuffer;
 use syn::spanned::Spanned as _;
 
@@ -29,6 +32,8 @@ pub use crate::errors::{DirectoryError as Error, DirectoryErrorKind as ErrorKind
 
 /***** CONSTANTS *****/
 /// Defines the list of extensions that we automatically recognize.
+/// 
+/// Note that this list is mirrored in `directories::naming`, since we cannot export it from a proc-macro crate. Keep them in sync!
 const EXTENSIONS: [ &str; 32 ] = [
     // Executable files
     "out", "exe",
@@ -54,6 +59,343 @@ const EXTENSIONS: [ &str; 32 ] = [
 
 
 /***** HELPER FUNCTIONS *****/
+/// Extracts the doc comment from the given list of attributes.
+/// 
+/// # Arguments
+/// - `attrs`: The list of attributes given at toplevel or at a field.
+/// 
+/// # Returns
+/// The doc comment, with every line on its own line, or [`None`] if there was none.
+fn parse_doc(attrs: impl AsRef<[Attribute]>) -> Option<String> {
+    let attrs: &[Attribute] = attrs.as_ref();
+
+    // Collect the lines of all `#[doc = "..."]` attributes
+    let mut lines: Vec<String> = vec![];
+    for attr in attrs {
+        if let Meta::NameValue(nv) = &attr.meta {
+            if !nv.path.is_ident("doc") { continue; }
+            if let Expr::Lit(lit) = &nv.value {
+                if let Lit::Str(s) = &lit.lit {
+                    // Strip the space that comes with `/// ...`
+                    let line: String = s.value();
+                    lines.push(line.strip_prefix(' ').map(String::from).unwrap_or(line));
+                }
+            }
+        }
+    }
+
+    // Only return if we found any
+    if !lines.is_empty() { Some(lines.join("\n")) } else { None }
+}
+
+/// Generates the tokens that build the [`directories::FieldSpec`] of a field from its attributes.
+/// 
+/// # Arguments
+/// - `kind`: The explicit kind of the field (`#[file]` or `#[dir]`), if any.
+/// - `mode`: The [`FieldMode`] of the field, to deduce the kind from its extension if no explicit one is given.
+/// - `contents`: The default [`FieldContents`] of the field, if any.
+/// - `unix_mode`: The permissions of the field, if any.
+/// - `symlink`: Whether the field must be a symlink, and if so, whether it must point inside the layout.
+/// 
+/// # Returns
+/// A [`TokenStream2`] that evaluates to a `directories::FieldSpec`.
+fn quote_spec(kind: Option<FieldKind>, mode: &FieldMode, contents: Option<&FieldContents>, unix_mode: Option<u32>, symlink: Option<bool>) -> TokenStream2 {
+    let kind = match (kind, mode) {
+        (Some(FieldKind::Dir), _)                                     => quote! { ::directories::schema::EntryKind::Dir },
+        (Some(FieldKind::File), _) | (None, FieldMode::Path(_, true)) => quote! { ::directories::schema::EntryKind::File },
+        (None, _)                                                     => quote! { ::directories::schema::EntryKind::Any },
+    };
+    let contents: Option<TokenStream2> = contents.map(|contents| match contents {
+        FieldContents::Literal(value)       => quote! { spec.contents = Some(::directories::Contents::Bytes(#value.as_bytes())); },
+        FieldContents::From(path)           => { let spath: String = path.display().to_string(); quote! { spec.contents = Some(::directories::Contents::Bytes(include_bytes!(#spath))); } },
+        FieldContents::Fn(f)                => quote! { spec.contents = Some(::directories::Contents::Fn(|| ::std::convert::Into::<Vec<u8>>::into((#f)()))); },
+        // Note: the span makes `include_str!()` resolve relative to the file with the attribute
+        FieldContents::Template(path, span) => { let include = quote_spanned! { *span=> include_str!(#path) }; quote! { spec.contents = Some(::directories::Contents::Template(#include)); } },
+    });
+    let unix_mode: Option<TokenStream2> = unix_mode.map(|m| quote! { spec.mode = Some(#m); });
+    let symlink: Option<TokenStream2> = symlink.map(|inside| match (inside, mode) {
+        // The layout is as many levels up as the path is long
+        (true, FieldMode::Path(path, _)) => { let depth: usize = path.components().count(); quote! { spec.symlink = Some(::directories::LinkTarget::Inside(#depth)); } },
+        _                                => quote! { spec.symlink = Some(::directories::LinkTarget::Anywhere); },
+    });
+
+    // Only build the spec in a block if we have to set anything
+    if contents.is_none() && unix_mode.is_none() && symlink.is_none() { return quote! { ::directories::FieldSpec::new(#kind) }; }
+    quote! {{
+        let mut spec: ::directories::FieldSpec = ::directories::FieldSpec::new(#kind);
+        #contents
+        #unix_mode
+        #symlink
+        spec
+    }}
+}
+
+/// Returns whether the given type is (syntactically) an [`Option`].
+/// 
+/// # Arguments
+/// - `ty`: The [`Type`] to examine.
+/// 
+/// # Returns
+/// True if the type's last path segment is `Option`, or false otherwise.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident == "Option").unwrap_or(false),
+        _             => false,
+    }
+}
+
+/// Checks a field against the directory given by `#[directories(check_against = "...")]`, emitting errors at the field if it does not match.
+/// 
+/// Optional fields are only checked if they exist, and flattened fields or fields with absolute paths are not checked at all. Nested layouts are not recursed into; give them their own `check_against` attribute instead.
+/// 
+/// # Arguments
+/// - `root`: The directory to check against.
+/// - `field`: The [`DirectoryField`] to check.
+fn check_field(root: &Path, field: &DirectoryField) {
+    let path: &Path = match &field.mode {
+        FieldMode::Path(path, _) if !path.is_absolute() => path,
+        _ => { return; },
+    };
+    let full: PathBuf = root.join(path);
+
+    // Check whether it exists at all
+    if !full.exists() {
+        if !is_option(&field.ty) {
+            Diagnostic::spanned(field.span, Level::Error, format!("Field '{}' refers to '{}', which does not exist", field.name, full.display())).emit();
+        }
+        return;
+    }
+
+    // If it does, check if it's of the correct kind
+    let kind: Option<FieldKind> = match (field.kind, &field.mode) {
+        (Some(kind), _)                  => Some(kind),
+        (None, FieldMode::Path(_, true)) => Some(FieldKind::File),
+        (None, _)                        => None,
+    };
+    match kind {
+        Some(FieldKind::File) if !full.is_file() => Diagnostic::spanned(field.span, Level::Error, format!("Field '{}' refers to '{}', which is not a file", field.name, full.display())).emit(),
+        Some(FieldKind::Dir) if !full.is_dir()   => Diagnostic::spanned(field.span, Level::Error, format!("Field '{}' refers to '{}', which is not a directory", field.name, full.display())).emit(),
+        _ => {},
+    }
+}
+
+/// Generates the tokens that embed the given file or directory in the binary, recursively.
+/// 
+/// # Arguments
+/// - `path`: The (absolute) path of the file or directory to embed.
+/// - `span`: The span to report errors at.
+/// 
+/// # Returns
+/// A [`TokenStream2`] that evaluates to a `directories::embed::EmbeddedEntry`, or [`None`] if we failed to read the directory (in which case an error has been emitted).
+fn quote_embedded(path: &Path, span: Span) -> Option<TokenStream2> {
+    // Files are simply included
+    if !path.is_dir() {
+        let spath: String = path.display().to_string();
+        return Some(quote! { ::directories::embed::EmbeddedEntry::File(include_bytes!(#spath)) });
+    }
+
+    // Directories are recursed into
+    let entries: Vec<TokenStream2> = quote_embedded_entries(path, &[], span)?;
+    Some(quote! { ::directories::embed::EmbeddedEntry::Dir(::directories::embed::EmbeddedDir(&[ #(#entries),* ])) })
+}
+
+/// Generates the tokens that embed the entries of the given directory in the binary, recursively.
+/// 
+/// # Arguments
+/// - `path`: The (absolute) path of the directory to embed.
+/// - `skip`: Any names of entries that should not be embedded.
+/// - `span`: The span to report errors at.
+/// 
+/// # Returns
+/// A list of [`TokenStream2`]s that each evaluate to a `(&'static str, directories::embed::EmbeddedEntry)`, or [`None`] if we failed to read the directory (in which case an error has been emitted).
+fn quote_embedded_entries(path: &Path, skip: &[&OsStr], span: Span) -> Option<Vec<TokenStream2>> {
+    // Collect the entries, sorted to be reproducible
+    let mut names: Vec<String> = vec![];
+    match fs::read_dir(path) {
+        Ok(entries) => for entry in entries {
+            match entry {
+                Ok(entry) => {
+                    if skip.contains(&entry.file_name().as_os_str()) { continue; }
+                    match entry.file_name().into_string() {
+                        Ok(name)  => names.push(name),
+                        Err(name) => {
+                            Diagnostic::spanned(span, Level::Error, format!("Cannot embed '{}', since its name is not valid UTF-8", path.join(name).display())).emit();
+                            return None;
+                        },
+                    }
+                },
+                Err(err) => {
+                    Diagnostic::spanned(span, Level::Error, format!("Failed to read entry in directory '{}' to embed: {err}", path.display())).emit();
+                    return None;
+                },
+            }
+        },
+        Err(err) => {
+            Diagnostic::spanned(span, Level::Error, format!("Failed to read directory '{}' to embed: {err}", path.display())).emit();
+            return None;
+        },
+    }
+    names.sort();
+
+    // Quote them
+    let mut entries: Vec<TokenStream2> = Vec::with_capacity(names.len());
+    for name in names {
+        let entry: TokenStream2 = quote_embedded(&path.join(&name), span)?;
+        entries.push(quote! { (#name, #entry) });
+    }
+    Some(entries)
+}
+
+/// Generates the companion type and methods for `#[directories(embed = "...")]`.
+/// 
+/// The companion type is named after the struct with `Embedded` appended, and holds the contents of every field: `&'static [u8]` for files, a `directories::embed::EmbeddedDir` for directories and an `Option<directories::embed::EmbeddedEntry>` for optional fields. Flattened fields embed everything in the directory that isn't claimed by any other field.
+/// 
+/// # Arguments
+/// - `ident`: The identifier of the struct.
+/// - `vis`: The visibility of the struct, which the companion type will share.
+/// - `generics`: The generics of the struct.
+/// - `fields`: The fields of the struct.
+/// - `dir`: The directory to embed, relative to `CARGO_MANIFEST_DIR`.
+/// - `span`: The span of the `embed` attribute, to report errors at.
+/// 
+/// # Returns
+/// A [`TokenStream2`] with the companion type and its impls. Errors are emitted, not returned.
+fn derive_embedded(ident: &Ident, vis: &Visibility, generics: &Generics, fields: &[DirectoryField], dir: &Path, span: Span) -> TokenStream2 {
+    let root: PathBuf = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(dir);
+    if !root.is_dir() {
+        Diagnostic::spanned(span, Level::Error, format!("Cannot embed '{}', since it is not a directory", root.display())).emit();
+        return quote! {};
+    }
+
+    // Find the entries in the root that are claimed by non-flattened fields
+    let claimed: Vec<&OsStr> = fields.iter().filter_map(|f| match &f.mode {
+        FieldMode::Path(path, _) => path.iter().next(),
+        FieldMode::Flatten       => None,
+    }).collect();
+
+    // Embed every field
+    let mut names: Vec<&Ident> = Vec::with_capacity(fields.len());
+    let mut types: Vec<TokenStream2> = Vec::with_capacity(fields.len());
+    let mut values: Vec<TokenStream2> = Vec::with_capacity(fields.len());
+    let mut extracts: Vec<TokenStream2> = Vec::with_capacity(fields.len());
+    for field in fields {
+        let name: &Ident = &field.name;
+        let (ty, value, extract): (TokenStream2, TokenStream2, TokenStream2) = match &field.mode {
+            FieldMode::Path(path, _) if path.is_absolute() => {
+                Diagnostic::spanned(field.span, Level::Error, format!("Cannot embed field '{name}', since it has an absolute path")).emit();
+                continue;
+            },
+
+            FieldMode::Path(path, _) => {
+                let full: PathBuf = root.join(path);
+                let spath: String = path.display().to_string();
+                if is_option(&field.ty) {
+                    let value: TokenStream2 = if full.exists() {
+                        let Some(entry) = quote_embedded(&full, field.span) else { continue; };
+                        quote! { Some(#entry) }
+                    } else {
+                        quote! { None }
+                    };
+                    (quote! { Option<::directories::embed::EmbeddedEntry> }, value, quote! {
+                        if let Some(entry) = &self.#name { entry.extract(base.join(#spath))?; }
+                    })
+                } else if full.is_file() {
+                    let sfull: String = full.display().to_string();
+                    (quote! { &'static [u8] }, quote! { include_bytes!(#sfull) }, quote! {
+                        ::directories::embed::extract_file(&base.join(#spath), self.#name)?;
+                    })
+                } else if full.is_dir() {
+                    let Some(entries) = quote_embedded_entries(&full, &[], field.span) else { continue; };
+                    (quote! { ::directories::embed::EmbeddedDir }, quote! { ::directories::embed::EmbeddedDir(&[ #(#entries),* ]) }, quote! {
+                        self.#name.extract(base.join(#spath))?;
+                    })
+                } else {
+                    Diagnostic::spanned(field.span, Level::Error, format!("Cannot embed field '{name}', since '{}' does not exist", full.display())).emit();
+                    continue;
+                }
+            },
+
+            FieldMode::Flatten => {
+                let Some(entries) = quote_embedded_entries(&root, &claimed, field.span) else { continue; };
+                (quote! { ::directories::embed::EmbeddedDir }, quote! { ::directories::embed::EmbeddedDir(&[ #(#entries),* ]) }, quote! {
+                    self.#name.extract(base)?;
+                })
+            },
+        };
+        names.push(name);
+        types.push(ty);
+        values.push(value);
+        extracts.push(extract);
+    }
+
+    // Generate the companion type
+    let cident: Ident = format_ident!("{}Embedded", ident);
+    let cdoc: String = format!("Holds the contents of a [`{ident}`], embedded in the binary at compile time from '{}'.", dir.display());
+    let fdocs: Vec<String> = names.iter().map(|n| format!("The embedded contents of `{n}`.")).collect();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[doc = #cdoc]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #cident {
+            #(
+                #[doc = #fdocs]
+                pub #names : #types,
+            )*
+        }
+        impl #cident {
+            /// Writes the embedded contents to the given directory, without overwriting anything that already exists.
+            /// 
+            /// # Arguments
+            /// - `base`: The directory to write the contents to.
+            /// 
+            /// # Errors
+            /// This function errors if we failed to create any of the directories or files.
+            pub fn extract(&self, base: impl AsRef<::std::path::Path>) -> Result<(), ::directories::Error> {
+                let base: &::std::path::Path = base.as_ref();
+                #(#extracts)*
+                Ok(())
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// The contents of this layout, embedded in the binary at compile time.
+            #vis const EMBEDDED: #cident = #cident {
+                #( #names : #values, )*
+            };
+
+            /// Writes the embedded contents of this layout to the given directory (without overwriting anything that already exists), and then initializes the layout in it.
+            /// 
+            /// # Arguments
+            /// - `base`: The directory to write the contents to.
+            /// 
+            /// # Returns
+            /// A new instance of Self, initialized in `base`.
+            /// 
+            /// # Errors
+            /// This function errors if we failed to write any of the contents, or if we failed to initialize the layout.
+            #vis fn extract(base: impl Into<::std::path::PathBuf>) -> Result<Self, ::directories::Error> {
+                let base: ::std::path::PathBuf = base.into();
+                Self::EMBEDDED.extract(&base)?;
+                <Self as ::directories::Directory>::try_init(base)
+            }
+        }
+    }
+}
+
+/// Turns an optional doc comment into tokens that build an `Option<String>`.
+/// 
+/// # Arguments
+/// - `doc`: The doc comment to quote.
+/// 
+/// # Returns
+/// A [`TokenStream2`] that evaluates to the given doc comment as an `Option<String>`.
+fn quote_doc(doc: Option<String>) -> TokenStream2 {
+    match doc {
+        Some(doc) => quote! { Some(#doc.into()) },
+        None      => quote! { None },
+    }
+}
+
 /// Extracts the information we want from the toplevel attributes.
 /// 
 /// # Arguments
@@ -122,6 +464,127 @@ fn parse_toplevel_attrs(attrs: impl AsRef<[Attribute]>) -> Result<DirectoryAttri
                             // Add it to the database
                             res.exts.insert(suffix, ext);
 
+                        } else if nv.path.is_ident("symlinks") {
+                            // It's the policy for symlinks found while scanning dynamic fields
+                            let policy: TokenStream2 = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) if s.value() == "follow" => quote! { ::directories::SymlinkPolicy::Follow },
+                                    Lit::Str(s) if s.value() == "skip"   => quote! { ::directories::SymlinkPolicy::Skip },
+                                    Lit::Str(s) if s.value() == "error"  => quote! { ::directories::SymlinkPolicy::Error },
+                                    _ => {
+                                        Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"follow\", \"skip\" or \"error\"".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"follow\", \"skip\" or \"error\"".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = &res.symlinks {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, "Duplicate '#[directories(symlinks)]' attribute".into()).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.symlinks = Some((policy, nv.value.span()));
+
+                        } else if nv.path.is_ident("names") {
+                            // It's the policy for non-UTF-8 names found while scanning dynamic fields
+                            let policy: TokenStream2 = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) if s.value() == "skip"  => quote! { ::directories::NamePolicy::Skip },
+                                    Lit::Str(s) if s.value() == "error" => quote! { ::directories::NamePolicy::Error },
+                                    _ => {
+                                        Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"skip\" or \"error\"".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"skip\" or \"error\"".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = &res.names {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, "Duplicate '#[directories(names)]' attribute".into()).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.names = Some((policy, nv.value.span()));
+
+                        } else if nv.path.is_ident("keys") {
+                            // It's the policy for names that fail to parse as keys while scanning dynamic fields
+                            let policy: TokenStream2 = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) if s.value() == "skip"    => quote! { ::directories::KeyPolicy::Skip },
+                                    Lit::Str(s) if s.value() == "error"   => quote! { ::directories::KeyPolicy::Error },
+                                    Lit::Str(s) if s.value() == "collect" => quote! { ::directories::KeyPolicy::Collect },
+                                    _ => {
+                                        Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"skip\", \"error\" or \"collect\"".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"skip\", \"error\" or \"collect\"".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = &res.keys {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, "Duplicate '#[directories(keys)]' attribute".into()).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.keys = Some((policy, nv.value.span()));
+
+                        } else if nv.path.is_ident("lockfile") {
+                            // It's the path of the lock file
+                            let value: String = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) => s.value(),
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = &res.lockfile {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, "Duplicate '#[directories(lockfile)]' attribute".into()).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.lockfile = Some((value, nv.value.span()));
+
+                        } else if nv.path.is_ident("check_against") || nv.path.is_ident("embed") {
+                            // It's a directory to check the layout against or to embed at compile time
+                            let value: String = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) => s.value(),
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            let (target, name): (&mut Option<(PathBuf, Span)>, &str) = if nv.path.is_ident("embed") { (&mut res.embed, "embed") } else { (&mut res.check_against, "check_against") };
+                            if let Some(old) = target {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, format!("Duplicate '#[directories({name})]' attribute")).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            *target = Some((value.into(), nv.value.span()));
+
                         } else {
                             Diagnostic::spanned(l.path.span(), Level::Error, format!("Unknown attribute{}", if let Some(i) = nv.path.get_ident() { format!(" '{}'", i.to_string()) } else { String::new() })).emit();
                             continue 'attrs;
@@ -132,7 +595,14 @@ fn parse_toplevel_attrs(attrs: impl AsRef<[Attribute]>) -> Result<DirectoryAttri
                             Diagnostic::spanned(l.path.span(), Level::Error, format!("Unknown attribute{}", if let Some(i) = l.path.get_ident() { format!(" '{}'", i.to_string()) } else { String::new() })).emit();
                             continue 'attrs;
                         },
-                        Meta::Path(p) => {
+                        Meta::Path(p) => if p.is_ident("schema") {
+                            // It's the opt-in to also describe the layout as a schema
+                            if let Some(old) = res.schema {
+                                Diagnostic::spanned(p.span(), Level::Warning, "Duplicate '#[directories(schema)]' attribute".into()).span_note(old, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.schema = Some(p.span());
+
+                        } else {
                             Diagnostic::spanned(p.span(), Level::Error, format!("Unknown attribute{}", if let Some(i) = p.get_ident() { format!(" '{}'", i.to_string()) } else { String::new() })).emit();
                             continue 'attrs;
                         },
@@ -170,6 +640,7 @@ fn parse_field_attrs(attrs: impl AsRef<[Attribute]>) -> Result<FieldAttributes,
         match &attr.meta {
             Meta::List(l) => if l.path.is_ident("file") || l.path.is_ident("dir") {
                 seen_file_dir = Some(l.span());
+                res.kind = Some(if l.path.is_ident("file") { FieldKind::File } else { FieldKind::Dir });
 
                 // Assert we haven't seen `#[this]` yet
                 if let Some(old) = res.this {
@@ -224,12 +695,174 @@ fn parse_field_attrs(attrs: impl AsRef<[Attribute]>) -> Result<FieldAttributes,
                                 },
                             };
 
+                            // Warn for paths that may escape the directory
+                            if Path::new(&value).components().any(|c| c == Component::ParentDir) {
+                                Diagnostic::spanned(nv.value.span(), Level::Warning, format!("Path '{value}' contains '..', which may refer to outside of the directory")).help("Use `Directory::try_init_confined()` to assert paths stay within the directory at runtime".into()).emit();
+                            }
+
                             // Now we have the path, add it for this identifier
                             if let Some(old) = res.path {
                                 Diagnostic::spanned(nv.path.span(), Level::Warning, format!("Duplicate '#[{}(path)]' attribute", l.path.get_ident().unwrap())).span_note(old.1, "Previous occurrence is given here".into()).emit();
                             }
                             res.path = Some((value.into(), nv.value.span()));
 
+                        } else if nv.path.is_ident("symlink") {
+                            // It's a symlink with a constraint on its target
+                            let inside: bool = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) if s.value() == "inside" => true,
+                                    Lit::Str(s) if s.value() == "anywhere" => false,
+                                    _ => {
+                                        Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"inside\" or \"anywhere\"".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(nv.value.span(), Level::Error, "Expected either \"inside\" or \"anywhere\"".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = res.symlink {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, format!("Duplicate '#[{}(symlink)]' attribute", l.path.get_ident().unwrap())).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.symlink = Some((inside, nv.path.span()));
+
+                        } else if nv.path.is_ident("glob") {
+                            // It's a pattern that the entries of a dynamic field must match
+                            let value: String = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) => s.value(),
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Patterns may be given multiple times, so simply add it
+                            res.globs.push((value, nv.value.span()));
+
+                        } else if nv.path.is_ident("regex") {
+                            // It's a regular expression that the entries of a dynamic field must match
+                            let value: String = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Str(s) => s.value(),
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it, but only if the `directories` crate has support for it
+                            #[cfg(feature = "regex")]
+                            {
+                                if let Some(old) = &res.regex {
+                                    Diagnostic::spanned(nv.path.span(), Level::Warning, format!("Duplicate '#[{}(regex)]' attribute", l.path.get_ident().unwrap())).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                                }
+                                res.regex = Some((value, nv.value.span()));
+                            }
+                            #[cfg(not(feature = "regex"))]
+                            {
+                                let _ = value;
+                                Diagnostic::spanned(nv.path.span(), Level::Error, format!("'#[{}(regex)]' requires the 'regex' feature of the 'directories' crate", l.path.get_ident().unwrap())).emit();
+                            }
+
+                        } else if nv.path.is_ident("mode") {
+                            // It's the permissions of the file or directory
+
+                            // Parse the value as an integer literal
+                            let value: u32 = match &nv.value {
+                                Expr::Lit(lit) => match &lit.lit {
+                                    Lit::Int(i) => match i.base10_parse::<u32>() {
+                                        Ok(value) if value <= 0o7777 => value,
+                                        _ => {
+                                            Diagnostic::spanned(i.span(), Level::Error, "Expected a mode between 0o0000 and 0o7777".into()).emit();
+                                            continue 'attrs;
+                                        },
+                                    },
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected integer literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                },
+
+                                _ => {
+                                    Diagnostic::spanned(l.path.span(), Level::Error, "Expected integer literal".into()).emit();
+                                    continue 'attrs;
+                                },
+                            };
+
+                            // Store it
+                            if let Some(old) = res.unix_mode {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, format!("Duplicate '#[{}(mode)]' attribute", l.path.get_ident().unwrap())).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.unix_mode = Some((value, nv.value.span()));
+
+                        } else if nv.path.is_ident("default") || nv.path.is_ident("default_from") || nv.path.is_ident("default_fn") || nv.path.is_ident("template") {
+                            // It's the default contents of the file
+
+                            // Assert this is a file
+                            if !l.path.is_ident("file") {
+                                Diagnostic::spanned(nv.path.span(), Level::Error, format!("Only '#[file]' fields can have '{}'", nv.path.get_ident().unwrap())).emit();
+                                continue 'attrs;
+                            }
+
+                            // Parse the value
+                            let contents: FieldContents = if nv.path.is_ident("default_fn") {
+                                // Any expression is accepted as function
+                                FieldContents::Fn(nv.value.clone())
+                            } else {
+                                // The others are string literals
+                                let value: String = match &nv.value {
+                                    Expr::Lit(lit) => match &lit.lit {
+                                        Lit::Str(s) => s.value(),
+                                        _ => {
+                                            Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                            continue 'attrs;
+                                        },
+                                    },
+
+                                    _ => {
+                                        Diagnostic::spanned(l.path.span(), Level::Error, "Expected string literal".into()).emit();
+                                        continue 'attrs;
+                                    },
+                                };
+
+                                // Resolve the path to read from relative to the crate
+                                if nv.path.is_ident("default_from") {
+                                    let path: PathBuf = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(value);
+                                    if !path.is_file() {
+                                        Diagnostic::spanned(nv.value.span(), Level::Error, format!("Cannot read default contents from '{}', since it is not a file", path.display())).emit();
+                                        continue 'attrs;
+                                    }
+                                    FieldContents::From(path)
+                                } else if nv.path.is_ident("template") {
+                                    FieldContents::Template(value, nv.value.span())
+                                } else {
+                                    FieldContents::Literal(value)
+                                }
+                            };
+
+                            // Store it
+                            if let Some(old) = res.contents {
+                                Diagnostic::spanned(nv.path.span(), Level::Warning, "Duplicate default contents for field".into()).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.contents = Some((contents, nv.path.span()));
+
                         } else {
                             Diagnostic::spanned(l.path.span(), Level::Error, format!("Unknown attribute{}", if let Some(i) = nv.path.get_ident() { format!(" '{}'", i.to_string()) } else { String::new() })).emit();
                             continue 'attrs;
@@ -249,6 +882,13 @@ fn parse_field_attrs(attrs: impl AsRef<[Attribute]>) -> Result<FieldAttributes,
                             }
                             res.flatten = Some(p.span());
 
+                        } else if p.is_ident("symlink") {
+                            // Mark this as a symlink that may point anywhere
+                            if let Some(old) = res.symlink {
+                                Diagnostic::spanned(p.span(), Level::Warning, format!("Duplicate '#[{}(symlink)]' attribute", l.path.get_ident().unwrap())).span_note(old.1, "Previous occurrence is given here".into()).emit();
+                            }
+                            res.symlink = Some((false, p.span()));
+
                         } else {
                             Diagnostic::spanned(p.span(), Level::Error, format!("Unknown attribute{}", if let Some(i) = p.get_ident() { format!(" '{}'", i.to_string()) } else { String::new() })).emit();
                             continue 'attrs;
@@ -263,8 +903,18 @@ fn parse_field_attrs(attrs: impl AsRef<[Attribute]>) -> Result<FieldAttributes,
                 }
             },
 
-            // Look for this
-            Meta::Path(p) => if p.is_ident("this") {
+            // Look for a bare `#[file]` or `#[dir]`, which only marks the kind of field
+            Meta::Path(p) => if p.is_ident("file") || p.is_ident("dir") {
+                seen_file_dir = Some(p.span());
+
+                // Assert we haven't seen `#[this]` yet
+                if let Some(old) = res.this {
+                    Diagnostic::spanned(p.span(), Level::Error, "Field cannot be both '#[file]'/'#[dir]' and '#[this]".into()).span_note(old, "Conflicting attribute given here".into()).emit();
+                    continue 'attrs;
+                }
+                res.kind = Some(if p.is_ident("file") { FieldKind::File } else { FieldKind::Dir });
+
+            } else if p.is_ident("this") {
                 // Assert this is not a `#[file]` or `#[dir]`
                 if let Some(old) = seen_file_dir {
                     Diagnostic::spanned(p.span(), Level::Error, "Field cannot be both '#[file]'/'#[dir]' and '#[this]".into()).span_note(old, "Conflicting attribute given here".into()).emit();
@@ -296,7 +946,21 @@ fn parse_field_attrs(attrs: impl AsRef<[Attribute]>) -> Result<FieldAttributes,
 #[derive(Clone, Debug)]
 struct DirectoryAttributes {
     /// The database of extensions for this directory.
-    exts : HashMap<String, String>,
+    exts          : HashMap<String, String>,
+    /// A directory (relative to `CARGO_MANIFEST_DIR`) to check the layout against at compile time.
+    check_against : Option<(PathBuf, Span)>,
+    /// A directory (relative to `CARGO_MANIFEST_DIR`) to embed in the binary at compile time.
+    embed         : Option<(PathBuf, Span)>,
+    /// The policy for symlinks found while scanning dynamic fields, as tokens evaluating to a `directories::SymlinkPolicy`.
+    symlinks      : Option<(TokenStream2, Span)>,
+    /// The policy for non-UTF-8 names found while scanning dynamic fields, as tokens evaluating to a `directories::NamePolicy`.
+    names         : Option<(TokenStream2, Span)>,
+    /// The policy for names that fail to parse as keys while scanning dynamic fields, as tokens evaluating to a `directories::KeyPolicy`.
+    keys          : Option<(TokenStream2, Span)>,
+    /// The path of the lock file, relative to the directory.
+    lockfile      : Option<(String, Span)>,
+    /// Whether to also implement `directories::Schema`, given by `#[directories(schema)]`.
+    schema        : Option<Span>,
 }
 impl DirectoryAttributes {
     /// Constructor for the DirectoryAttributes that initializes it to empty (nothing parsed).
@@ -306,7 +970,14 @@ impl DirectoryAttributes {
     #[inline]
     fn empty() -> Self {
         Self {
-            exts : EXTENSIONS.iter().map(|e| (format!("_{e}"), format!(".{e}"))).collect(),
+            exts          : EXTENSIONS.iter().map(|e| (format!("_{e}"), format!(".{e}"))).collect(),
+            check_against : None,
+            embed         : None,
+            symlinks      : None,
+            names         : None,
+            keys          : None,
+            lockfile      : None,
+            schema        : None,
         }
     }
 }
@@ -320,38 +991,91 @@ struct DirectoryField {
     name : Ident,
     /// Defines the type of this field.
     ty   : Type,
+    /// Defines the doc comment of this field, if any.
+    doc  : Option<String>,
 
     /// Defines the mode of the field.
     mode : FieldMode,
+    /// Defines whether this field is explicitly a file or a directory.
+    kind      : Option<FieldKind>,
+    /// Defines the default contents of this field, if it's a file.
+    contents  : Option<FieldContents>,
+    /// Defines the permissions this field must have.
+    unix_mode : Option<u32>,
+    /// Defines whether this field must be a symlink, and if so, whether it must point inside the layout.
+    symlink   : Option<bool>,
+    /// Defines the glob patterns that the entries of this field must match, if it's dynamic.
+    globs     : Vec<String>,
+    /// Defines the regular expression that the entries of this field must match, if it's dynamic.
+    regex     : Option<String>,
+    /// The span to report errors about the field's path at (i.e., the given path or the field name).
+    span : Span,
 }
 
 #[derive(Clone, Debug, EnumDebug)]
 enum FieldMode {
-    /// It's a path, as per usual
-    Path(PathBuf),
+    /// It's a path, as per usual. The boolean indicates whether it has been deduced from a known extension.
+    Path(PathBuf, bool),
     /// It's flattened.
     Flatten,
 }
 
+/// Defines the default contents of a file field, as given by `#[file(default...)]`.
+#[derive(Clone, EnumDebug)]
+enum FieldContents {
+    /// It's given as a string literal (`default = "..."`).
+    Literal(String),
+    /// It's read from the file at the given (absolute) path at compile time (`default_from = "..."`).
+    From(PathBuf),
+    /// It's generated by calling the given function (`default_fn = ...`).
+    Fn(Expr),
+    /// It's rendered from the template at the given path, relative to the source file (`template = "..."`).
+    Template(String, Span),
+}
+
+/// Defines what a field refers to on disk, as given by `#[file]` or `#[dir]`.
+#[derive(Clone, Copy, Debug, EnumDebug)]
+enum FieldKind {
+    /// It's a file (`#[file]`).
+    File,
+    /// It's a directory (`#[dir]`).
+    Dir,
+}
+
 
 /// Defines everything we want to know of the `#[this]`-field.
 #[derive(Clone)]
 struct ThisField {
     /// Defines the identifier of the field.
     name : Ident,
+    /// Defines the doc comment of this field, if any.
+    doc  : Option<String>,
 }
 
 
 /// Defines everything we might learn from field attributes.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 struct FieldAttributes {
     /// If true, then this is the `#[this]` field.
     this : Option<Span>,
+    /// Whether this is explicitly a `#[file]` or a `#[dir]`.
+    kind : Option<FieldKind>,
 
     /// Whether the field is flattened or not.
     flatten : Option<Span>,
     /// An override for the default derived path.
     path    : Option<(PathBuf, Span)>,
+
+    /// The default contents of the field, if it's a file.
+    contents  : Option<(FieldContents, Span)>,
+    /// The permissions the field must have.
+    unix_mode : Option<(u32, Span)>,
+    /// Whether the field must be a symlink, and if so, whether it must point inside the layout.
+    symlink   : Option<(bool, Span)>,
+    /// The glob patterns that the entries of a dynamic field must match.
+    globs     : Vec<(String, Span)>,
+    /// The regular expression that the entries of a dynamic field must match.
+    regex     : Option<(String, Span)>,
 }
 impl FieldAttributes {
     /// Constructor for the FieldAttributes that initializes it to empty (nothing parsed).
@@ -362,9 +1086,16 @@ impl FieldAttributes {
     fn empty() -> Self {
         Self {
             this : None,
+            kind : None,
 
             flatten : None,
             path    : None,
+
+            contents  : None,
+            unix_mode : None,
+            symlink   : None,
+            globs     : vec![],
+            regex     : None,
         }
     }
 }
@@ -390,7 +1121,7 @@ impl FieldAttributes {
 /// This function may error if it failed to parse the input properly.
 /// 
 /// Note that some non-fatal errors or warnings may be emitted during execution of this function.
-pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generics, _vis: Visibility) -> Result<TokenStream, Error<'static>> {
+pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generics, vis: Visibility) -> Result<TokenStream, Error<'static>> {
     // First: let's extract this as a struct
     let data: DataStruct = match data {
         Data::Struct(s) => s,
@@ -399,6 +1130,7 @@ pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generic
     };
 
     // Next, we can collect any main struct attributes
+    let doc: Option<String> = parse_doc(&attrs);
     let dir_attrs: DirectoryAttributes = parse_toplevel_attrs(attrs)?;
 
     // Time to dive into the struct's fields and get the information we need
@@ -408,6 +1140,7 @@ pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generic
     for field in data.fields {
         // Parse the field attributes to find anything interesting
         let attrs: FieldAttributes = parse_field_attrs(&field.attrs)?;
+        let doc: Option<String> = parse_doc(&field.attrs);
 
         // Extract the name of the field
         let name: Ident = match field.ident {
@@ -430,82 +1163,215 @@ pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generic
             // Now set it
             this = Some(ThisField {
                 name,
+                doc,
             });
 
         } else if attrs.flatten.is_some() {
+            // Flattened fields have no path of their own
+            if let Some((_, span)) = attrs.symlink {
+                Diagnostic::spanned(span, Level::Error, "Flattened fields cannot be symlinks".into()).emit();
+            }
+
             // Mark it as a flattened thing
             fields.push(DirectoryField {
+                span : name.span(),
                 name,
                 ty : field.ty,
-
-                mode : FieldMode::Flatten,
+                doc,
+
+                mode      : FieldMode::Flatten,
+                kind      : attrs.kind,
+                contents  : attrs.contents.map(|(c, _)| c),
+                unix_mode : attrs.unix_mode.map(|(m, _)| m),
+                symlink   : None,
+                globs     : attrs.globs.into_iter().map(|(g, _)| g).collect(),
+                regex     : attrs.regex.map(|(r, _)| r),
             });
 
         } else {
             // Deduce a default path if not given
-            let path: PathBuf =  attrs.path.map(|(p, _)| p).unwrap_or_else(|| {
-                // See if the name ends in a particular suffix
+            let span: Span = attrs.path.as_ref().map(|(_, s)| *s).unwrap_or_else(|| name.span());
+            let (path, from_ext): (PathBuf, bool) = attrs.path.map(|(p, _)| (p, false)).unwrap_or_else(|| {
+                // See if the name ends in a particular suffix (the longest, so that it doesn't depend on the order of the map)
                 let name: String = name.to_string();
                 let name_len: usize = name.len();
-                for (suffix, ext) in &dir_attrs.exts {
-                    if name.ends_with(suffix) {
-                        return format!("{}{}", &name[..name_len - suffix.len()], ext).into();
-                    }
+                if let Some((suffix, ext)) = dir_attrs.exts.iter().filter(|(suffix, _)| name.ends_with(suffix.as_str())).max_by_key(|(suffix, _)| suffix.len()) {
+                    return (format!("{}{}", &name[..name_len - suffix.len()], ext).into(), true);
                 }
 
                 // Otherwise, just return the name
-                PathBuf::from(&name)
+                (PathBuf::from(&name), false)
             });
 
             // Construct the final type and add it
             fields.push(DirectoryField {
                 name,
                 ty : field.ty,
-
-                mode : FieldMode::Path(path),
+                doc,
+
+                mode      : FieldMode::Path(path, from_ext),
+                kind      : attrs.kind,
+                contents  : attrs.contents.map(|(c, _)| c),
+                unix_mode : attrs.unix_mode.map(|(m, _)| m),
+                symlink   : attrs.symlink.map(|(i, _)| i),
+                globs     : attrs.globs.into_iter().map(|(g, _)| g).collect(),
+                regex     : attrs.regex.map(|(r, _)| r),
+                span,
             });
         }
     }
 
+    // If told to, check the fields against the directory on disk
+    if let Some((dir, span)) = &dir_attrs.check_against {
+        // Note: the proc macro is not re-run if only the directory changes, only if the source does
+        let root: PathBuf = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(dir);
+        if root.is_dir() {
+            for field in &fields {
+                check_field(&root, field);
+            }
+        } else {
+            Diagnostic::spanned(*span, Level::Error, format!("Cannot check layout against '{}', since it is not a directory", root.display())).emit();
+        }
+    }
+
+    // If told to, embed the fields' contents in a companion type
+    let embedded: Option<TokenStream2> = dir_attrs.embed.as_ref().map(|(dir, span)| derive_embedded(&ident, &vis, &generics, &fields, dir, *span));
+
+    // Resolve the lock file relative to the `#[this]`-field, since that's the only way we know our own path
+    let lockfile = match (&dir_attrs.lockfile, &this) {
+        (Some((lockfile, _)), Some(ThisField { name, .. })) => Some(quote! {
+            #[inline]
+            fn lockfile(&self) -> Option<::std::path::PathBuf> { Some(self.#name.join(#lockfile)) }
+        }),
+        (Some((_, span)), None) => {
+            Diagnostic::spanned(*span, Level::Error, "A lock file requires a '#[this]' field to resolve it against".into()).emit();
+            None
+        },
+        (None, _) => None,
+    };
+    let slockfile = match &dir_attrs.lockfile {
+        Some((lockfile, _)) => quote! { Some(#lockfile.into()) },
+        None                => quote! { None },
+    };
+
     // Change the this into initialization
     let this_init: Option<_> = this.as_ref().map(|t| {
-        let ThisField { name } = t;
+        let ThisField { name, .. } = t;
         quote! {
             #name : _base,
         }
     });
-    let this_exists: Option<_> = this.map(|t| {
-        let ThisField { name } = t;
+    let this_exists: Option<_> = this.as_ref().map(|t| {
+        let ThisField { name, .. } = t;
         quote! {
             exists &= <PathBuf as ::directories::DirectoryExt>::exists(&self.#name);
         }
     });
+    let this_walk: Option<_> = this.as_ref().map(|t| {
+        let ThisField { name, .. } = t;
+        quote! {
+            visit(&self.#name, &{
+                let mut spec: ::directories::FieldSpec = *_spec;
+                spec.kind = ::directories::schema::EntryKind::Dir;
+                spec
+            })?;
+        }
+    });
+    let this_schema: Option<_> = this.map(|t| {
+        let ThisField { name, doc } = t;
+        let sname: String = name.to_string();
+        let doc = quote_doc(doc);
+        quote! {
+            {
+                let mut field = ::directories::schema::FieldSchema::new(#sname, None, ::directories::schema::EntrySchema::default());
+                field.kind = ::directories::schema::EntryKind::Dir;
+                field.doc = #doc;
+                field
+            },
+        }
+    });
 
     // Change the fields into field initializations
     let mut fields_init: Vec<_> = Vec::with_capacity(fields.len());
     let mut fields_exists: Vec<_> = Vec::with_capacity(fields.len());
+    let mut fields_walk: Vec<_> = Vec::with_capacity(fields.len());
+    let mut fields_schema: Vec<_> = Vec::with_capacity(fields.len());
     for field in fields {
-        let DirectoryField { name, ty, mode } = field;
+        let DirectoryField { name, ty, doc, mode, kind, contents, unix_mode, symlink, globs, regex, .. } = field;
+
+        // Deduce the schema of this field; an explicit kind wins over one deduced from the type, which in turn wins over one deduced from the extension
+        let sname: String = name.to_string();
+        let spath = match &mode {
+            FieldMode::Path(path, _) => { let spath: String = path.display().to_string(); quote! { Some(#spath.into()) } },
+            FieldMode::Flatten       => quote! { None },
+        };
+        let skind = match (kind, &mode) {
+            (Some(FieldKind::File), _)       => Some(quote! { field.kind = ::directories::schema::EntryKind::File; }),
+            (Some(FieldKind::Dir), _)        => Some(quote! { field.kind = ::directories::schema::EntryKind::Dir; }),
+            (None, FieldMode::Path(_, true)) => Some(quote! {
+                if field.kind == ::directories::schema::EntryKind::Any { field.kind = ::directories::schema::EntryKind::File; }
+            }),
+            (None, _)                        => None,
+        };
+        let doc = quote_doc(doc);
+        fields_schema.push(quote! {
+            {
+                let mut field = ::directories::schema::FieldSchema::new(#sname, #spath, <#ty as ::directories::Schema>::schema());
+                #skind
+                field.doc = #doc;
+                field
+            },
+        });
 
         // We can already deduce the exists
         fields_exists.push(quote! {
             exists &= <#ty as ::directories::DirectoryExt>::exists(&self.#name);
         });
 
+        // Walk the field with a spec built from its attributes
+        let spec = quote_spec(kind, &mode, contents.as_ref(), unix_mode, symlink);
+        fields_walk.push(quote! {
+            <#ty as ::directories::DirectoryExt>::walk(&self.#name, &#spec, visit)?;
+        });
+
+        // Only collect entries matching the glob patterns or regular expression, if any
+        let glob_init = if !globs.is_empty() {
+            quote! { let _opts: &::directories::InitOptions = &_opts.with_glob(&path, ::directories::glob::Patterns::new([#(#globs),*])); }
+        } else {
+            quote! {}
+        };
+        // (The regular expression is compiled once and then cached in a static, which is local to the field's initialization)
+        let regex_init = regex.as_ref().map(|regex| quote! {
+            static REGEX: ::std::sync::OnceLock<::directories::captures::Regex> = ::std::sync::OnceLock::new();
+            let _opts: &::directories::InitOptions = &_opts.with_regex_cached(&path, #regex, &REGEX)?;
+        });
+
         // Match on what to do for the instantiation
         match mode {
-            FieldMode::Path(path) => {
+            FieldMode::Path(path, _) => {
                 // Preprocess the path
                 let spath: String = path.display().to_string();
 
                 // Generate the instantiation, which may differ based on whether the path is absolute or not
                 if path.is_absolute() {
                     fields_init.push(quote! {
-                        #name : <#ty as ::directories::Directory>::try_init(#spath)?,
+                        #name : {
+                            let path: ::std::path::PathBuf = ::std::path::PathBuf::from(#spath);
+                            _opts.check_confined(&path)?;
+                            #glob_init
+                            #regex_init
+                            <#ty as ::directories::Directory>::try_init_with(path, _opts)?
+                        },
                     });
                 } else {
                     fields_init.push(quote! {
-                        #name : <#ty as ::directories::Directory>::try_init(_base.join(#spath))?,
+                        #name : {
+                            let path: ::std::path::PathBuf = _base.join(#spath);
+                            _opts.check_confined(&path)?;
+                            #glob_init
+                            #regex_init
+                            <#ty as ::directories::Directory>::try_init_with(path, _opts)?
+                        },
                     });
                 }
             },
@@ -513,23 +1379,78 @@ pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generic
             FieldMode::Flatten => {
                 // Generate the instantiation, which just clones base
                 fields_init.push(quote! {
-                    #name : <#ty as ::directories::Directory>::try_init(_base.clone())?,
+                    #name : {
+                        let path: ::std::path::PathBuf = _base.clone();
+                        #glob_init
+                        #regex_init
+                        <#ty as ::directories::Directory>::try_init_with(path, _opts)?
+                    },
                 });
             }
         }
     }
 
+    // Override the options for nested fields if told to
+    let opts_init = if dir_attrs.symlinks.is_some() || dir_attrs.names.is_some() || dir_attrs.keys.is_some() {
+        let symlinks = dir_attrs.symlinks.as_ref().map(|(policy, _)| quote! { opts.symlinks = #policy; });
+        let names = dir_attrs.names.as_ref().map(|(policy, _)| quote! { opts.names = #policy; });
+        let keys = dir_attrs.keys.as_ref().map(|(policy, _)| quote! { opts.keys = #policy; });
+        quote! {
+            let _opts: &::directories::InitOptions = &{
+                let mut opts: ::directories::InitOptions = opts.clone();
+                #symlinks
+                #names
+                #keys
+                opts
+            };
+        }
+    } else {
+        quote! { let _opts: &::directories::InitOptions = opts; }
+    };
+
     // Get the generics of this struct for writing the new tokenstream
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let sident: String = ident.to_string();
+    let doc = quote_doc(doc);
+
+    // Only describe the layout if asked to, since it requires every field's type to implement `Schema` too
+    let schema: Option<TokenStream2> = dir_attrs.schema.map(|_| quote! {
+        #[automatically_derived]
+        impl #impl_generics ::directories::Schema for #ident #ty_generics #where_clause {
+            fn schema() -> ::directories::schema::EntrySchema {
+                ::directories::schema::EntrySchema {
+                    kind     : ::directories::schema::EntryKind::Dir,
+                    optional : false,
+                    dynamic  : false,
+                    nested   : Some(::directories::schema::LayoutSchema {
+                        name     : Some(#sident.into()),
+                        doc      : #doc,
+                        exts     : vec![],
+                        lockfile : #slockfile,
+                        fields   : vec![
+                            #this_schema
+                            #(#fields_schema)*
+                        ],
+                    }),
+                }
+            }
+        }
+    });
 
     // Now return the impls we need
     Ok(quote! {
+        #embedded
+
         #[automatically_derived]
         impl #impl_generics ::directories::Directory for #ident #ty_generics #where_clause {
             type Error = ::directories::Error;
 
-            fn try_init(base: impl Into<::std::path::PathBuf>) -> Result<Self, Self::Error> {
+            #[inline]
+            fn try_init(base: impl Into<::std::path::PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &::directories::InitOptions::default()) }
+
+            fn try_init_with(base: impl Into<::std::path::PathBuf>, opts: &::directories::InitOptions) -> Result<Self, Self::Error> {
                 let _base: ::std::path::PathBuf = base.into();
+                #opts_init
                 Ok(Self {
                     // Populate the normal fields
                     #(#fields_init)*
@@ -547,6 +1468,16 @@ pub fn derive(ident: Ident, data: Data, attrs: Vec<Attribute>, generics: Generic
                 #(#fields_exists)*
                 exists
             }
+
+            #lockfile
+
+            fn walk<E>(&self, _spec: &::directories::FieldSpec, visit: &mut dyn FnMut(&::std::path::Path, &::directories::FieldSpec) -> Result<(), E>) -> Result<(), E> {
+                #this_walk
+                #(#fields_walk)*
+                Ok(())
+            }
         }
+
+        #schema
     }.into())
 }
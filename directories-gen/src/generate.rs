@@ -0,0 +1,344 @@
+//  GENERATE.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:55:57
+//  Last edited:
+//    18 Oct 2026, 21:55:57
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Generates `#[derive(Directory)]` structs from a scanned
+//!   [`Node`] tree.
+// 
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use directories::naming::{default_exts, deduce_path, parse_ext};
+
+use crate::tree::{repeated, Node};
+
+
+/***** CONSTANTS *****/
+/// The keywords which we cannot use as field names.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+    "union",
+];
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Splits a name on disk into words, breaking on non-alphanumeric characters and on lowercase-to-uppercase boundaries.
+/// 
+/// # Arguments
+/// - `name`: The name to split.
+/// 
+/// # Returns
+/// A list of the (non-empty) words in the name.
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    let mut word: String = String::new();
+    let mut prev_lower: bool = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() { words.push(std::mem::take(&mut word)); }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower { words.push(std::mem::take(&mut word)); }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        word.push(c);
+    }
+    if !word.is_empty() { words.push(word); }
+    words
+}
+
+/// Converts a name on disk to a `snake_case` field identifier.
+/// 
+/// # Arguments
+/// - `name`: The name to convert.
+/// 
+/// # Returns
+/// A valid Rust identifier for a field.
+fn field_ident(name: &str) -> String {
+    let mut ident: String = words(name).into_iter().map(|w| w.to_ascii_lowercase()).collect::<Vec<String>>().join("_");
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) { ident.insert(0, '_'); }
+    if KEYWORDS.contains(&ident.as_str()) { ident.push('_'); }
+    ident
+}
+
+/// Converts a name on disk to a `CamelCase` type identifier.
+/// 
+/// # Arguments
+/// - `name`: The name to convert.
+/// 
+/// # Returns
+/// A valid Rust identifier for a type.
+fn type_ident(name: &str) -> String {
+    let mut ident: String = String::new();
+    for word in words(name) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            ident.push(first.to_ascii_uppercase());
+            ident.extend(chars);
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) { ident.insert(0, 'D'); }
+    ident
+}
+
+/// Returns the extension of a file name if it is one we could express as a field suffix.
+/// 
+/// # Arguments
+/// - `name`: The name of the file.
+/// 
+/// # Returns
+/// The extension (without dot), or [`None`] if the file has none or it's not a valid identifier.
+fn extension(name: &str) -> Option<&str> {
+    let (stem, ext): (&str, &str) = name.rsplit_once('.')?;
+    if stem.is_empty() || ext.is_empty() || !ext.chars().all(|c| c.is_ascii_alphanumeric()) { return None; }
+    Some(ext)
+}
+
+/// Naively turns a plural name into a singular one (e.g., `test_cases` -> `test_case`).
+/// 
+/// # Arguments
+/// - `name`: The name to singularize.
+/// 
+/// # Returns
+/// The singular name, or the name with `_entry` appended if we don't know how to make it singular.
+fn singular(name: &str) -> String {
+    match name.strip_suffix('s') {
+        Some(stem) if !stem.is_empty() && !stem.ends_with('s') => stem.into(),
+        _ => format!("{name}_entry"),
+    }
+}
+
+/// Makes an identifier unique by appending a number to it if it has been used already.
+/// 
+/// # Arguments
+/// - `ident`: The identifier to make unique.
+/// - `used`: The set of identifiers used so far. `ident` will be added to it.
+/// 
+/// # Returns
+/// The (possibly renamed) identifier.
+fn unique(ident: String, used: &mut HashSet<String>) -> String {
+    let mut res: String = ident.clone();
+    let mut i: usize = 2;
+    while used.contains(&res) {
+        res = format!("{ident}_{i}");
+        i += 1;
+    }
+    used.insert(res.clone());
+    res
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates `#[derive(Directory)]` structs from a scanned tree.
+#[derive(Debug)]
+pub struct Generator {
+    /// The structs generated so far, in order of definition.
+    structs      : Vec<String>,
+    /// The names of the structs generated so far.
+    struct_names : HashSet<String>,
+    /// Whether we used [`directories::std::Dynamic`] anywhere.
+    uses_dynamic : bool,
+}
+impl Generator {
+    /// Generates the layout of a directory tree.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the struct describing the root directory.
+    /// - `source`: The path the tree was scanned from. Only used for documentation.
+    /// - `root`: The scanned root directory.
+    /// 
+    /// # Returns
+    /// The Rust source code with all the structs that describe the tree.
+    pub fn generate(name: &str, source: &str, root: &BTreeMap<String, Node>) -> String {
+        let mut gen: Self = Self { structs: vec![], struct_names: HashSet::new(), uses_dynamic: false };
+        gen.gen_struct(name.into(), format!("Defines the layout of '{source}'."), root);
+
+        // Write the imports and the structs
+        let mut res: String = "use std::path::PathBuf;\n\nuse directories::Directory;\n".into();
+        if gen.uses_dynamic { res.push_str("use directories::std::Dynamic;\n"); }
+        for s in gen.structs {
+            res.push_str("\n\n");
+            res.push_str(&s);
+        }
+        res
+    }
+
+
+
+    /// Generates a struct for the given directory, and any nested structs it needs.
+    /// 
+    /// # Arguments
+    /// - `name`: The preferred name of the struct. Will be made unique.
+    /// - `doc`: The doc comment for the struct.
+    /// - `entries`: The entries in the directory.
+    /// 
+    /// # Returns
+    /// The name of the generated struct.
+    fn gen_struct(&mut self, name: String, doc: String, entries: &BTreeMap<String, Node>) -> String {
+        // Claim our name and our place (so that parents are written before their children)
+        let name: String = unique(name, &mut self.struct_names);
+        let index: usize = self.structs.len();
+        self.structs.push(String::new());
+
+        // Find any extensions we need to add to be able to use suffixes for them
+        let mut exts: Vec<(String, String)> = default_exts();
+        let mut custom: BTreeSet<String> = BTreeSet::new();
+        for (entry, node) in entries {
+            if !matches!(node, Node::File) { continue; }
+            if let Some(ext) = extension(entry) {
+                let (suffix, dext): (String, String) = parse_ext(ext);
+                if !exts.iter().any(|(s, _)| *s == suffix) {
+                    exts.push((suffix, dext));
+                    custom.insert(ext.into());
+                }
+            }
+        }
+
+        // See if any nested directories are repeated
+        let repeats: Option<&BTreeMap<String, Node>> = repeated(entries);
+
+        // Generate the fields
+        let mut fields: Vec<String> = vec![];
+        let mut idents: HashSet<String> = HashSet::new();
+        for (entry, node) in entries {
+            // Repeated directories are collected in a single field at the end
+            if repeats.is_some() && matches!(node, Node::Dir(_)) { continue; }
+
+            // Generate the field's name and see if we can deduce the path from it
+            let ident: String = unique(field_ident(entry), &mut idents);
+            let path: Option<&str> = if deduce_path(&ident, &exts) != *entry { Some(entry) } else { None };
+
+            // Generate the field based on what it is
+            let (attr, doc, ty): (Option<String>, String, String) = match node {
+                Node::File => {
+                    let attr: Option<String> = match path {
+                        Some(path) => Some(format!("#[file(path = {path:?})]")),
+                        None if extension(entry).is_none() => Some("#[file]".into()),
+                        None => None,
+                    };
+                    (attr, format!("The '{entry}' file."), "PathBuf".into())
+                },
+
+                Node::Dir(nested) if nested.is_empty() => {
+                    let attr: String = match path {
+                        Some(path) => format!("#[dir(path = {path:?})]"),
+                        None       => "#[dir]".into(),
+                    };
+                    (Some(attr), format!("The '{entry}' directory."), "PathBuf".into())
+                },
+
+                Node::Dir(nested) => {
+                    let attr: Option<String> = path.map(|path| format!("#[dir(path = {path:?})]"));
+                    match (repeated(nested), nested.values().all(|n| matches!(n, Node::Dir(_)))) {
+                        // A directory of only repeated directories becomes a dynamic field directly
+                        (Some(elem), true) => {
+                            let elem: String = self.gen_struct(format!("{}Dir", type_ident(&singular(entry))), format!("Defines the layout of every directory in '{entry}'."), elem);
+                            self.uses_dynamic = true;
+                            (attr, format!("The directories in '{entry}', which all share the same layout."), format!("Dynamic<{elem}>"))
+                        },
+
+                        // Otherwise, it's a nested struct
+                        _ => {
+                            let nested: String = self.gen_struct(format!("{}Dir", type_ident(entry)), format!("Defines the layout of the '{entry}' directory."), nested);
+                            (attr, format!("The '{entry}' directory."), nested)
+                        },
+                    }
+                },
+            };
+
+            // Write it
+            let mut field: String = format!("    /// {doc}\n");
+            if let Some(attr) = attr { field.push_str(&format!("    {attr}\n")); }
+            field.push_str(&format!("    {ident} : {ty},\n"));
+            fields.push(field);
+        }
+
+        // Add the repeated directories as a flattened dynamic field
+        if let Some(elem) = repeats {
+            let elem: String = self.gen_struct(format!("{}EntryDir", name.strip_suffix("Dir").unwrap_or(&name)), format!("Defines the layout of the repeated directories in {name}."), elem);
+            let ident: String = unique("entries".into(), &mut idents);
+            self.uses_dynamic = true;
+            fields.push(format!("    /// The nested directories, which all share the same layout.\n    #[dir(flatten)]\n    {ident} : Dynamic<{elem}>,\n"));
+        }
+
+        // Write the struct itself
+        let mut res: String = format!("/// {doc}\n#[derive(Debug, Directory)]\n");
+        if !custom.is_empty() {
+            res.push_str(&format!("#[directories({})]\n", custom.iter().map(|e| format!("ext = {e:?}")).collect::<Vec<String>>().join(", ")));
+        }
+        res.push_str(&format!("struct {name} {{\n{}}}", fields.join("")));
+        self.structs[index] = res;
+        name
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shorthand for the entries of a directory.
+    fn entries<const N: usize>(entries: [(&str, Node); N]) -> BTreeMap<String, Node> { entries.into_iter().map(|(name, node)| (name.to_string(), node)).collect() }
+
+
+    #[test]
+    fn test_idents() {
+        assert_eq!(words("fooBar-baz_2"), ["foo", "Bar", "baz", "2"]);
+        assert_eq!(field_ident("Test Cases"), "test_cases");
+        assert_eq!(field_ident("2023"), "_2023");
+        assert_eq!(field_ident("type"), "type_");
+        assert_eq!(type_ident("test-cases"), "TestCases");
+        assert_eq!(type_ident("2023"), "D2023");
+        assert_eq!(extension("main.rs"), Some("rs"));
+        assert_eq!(extension(".gitignore"), None);
+        assert_eq!(singular("plugins"), "plugin");
+        assert_eq!(singular("class"), "class_entry");
+
+        let mut used: HashSet<String> = HashSet::new();
+        assert_eq!(unique("data".into(), &mut used), "data");
+        assert_eq!(unique("data".into(), &mut used), "data_2");
+    }
+
+    #[test]
+    fn test_generate() {
+        let plugin = || Node::Dir(entries([("main.rs", Node::File)]));
+        let root: BTreeMap<String, Node> = entries([
+            ("config.json", Node::File),
+            ("LICENSE", Node::File),
+            ("save.world", Node::File),
+            ("plugins", Node::Dir(entries([("a", plugin()), ("b", plugin())]))),
+        ]);
+        let code: String = Generator::generate("Workspace", "/tmp/workspace", &root);
+
+        // Paths are deduced from the names where possible, also for custom extensions
+        assert!(code.contains("use directories::std::Dynamic;\n"));
+        assert!(code.contains("/// Defines the layout of '/tmp/workspace'.\n#[derive(Debug, Directory)]\n#[directories(ext = \"world\")]\nstruct Workspace {\n"));
+        assert!(code.contains("    /// The 'config.json' file.\n    config_json : PathBuf,\n"));
+        assert!(code.contains("    #[file(path = \"LICENSE\")]\n    license : PathBuf,\n"));
+        assert!(code.contains("    save_world : PathBuf,\n"));
+        // Repeated directories become a dynamic field
+        assert!(code.contains("    plugins : Dynamic<PluginDir>,\n"));
+        assert!(code.contains("struct PluginDir {\n    /// The 'main.rs' file.\n    main_rs : PathBuf,\n}"));
+
+        // Generating is deterministic
+        assert_eq!(code, Generator::generate("Workspace", "/tmp/workspace", &root));
+    }
+}
@@ -0,0 +1,87 @@
+//  MAIN.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:55:57
+//  Last edited:
+//    18 Oct 2026, 21:55:57
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Entrypoint to the `directories-gen` binary, which generates
+//!   `#[derive(Directory)]` structs matching an existing directory tree.
+// 
+
+// Declare the submodules
+mod generate;
+mod tree;
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Parser;
+use humanlog::{DebugMode, HumanLogger};
+use log::{error, info};
+
+use crate::generate::Generator;
+use crate::tree::Node;
+
+
+/***** ARGUMENTS *****/
+/// Defines the arguments for the `directories-gen` binary.
+#[derive(Parser)]
+#[clap(about = "Generates `#[derive(Directory)]` structs that match an existing directory tree.")]
+struct Arguments {
+    /// Whether to enable full debug structures or not
+    #[clap(long, global=true, help="If given, enables additional logging statements.")]
+    debug : bool,
+
+    /// The directory to scan.
+    #[clap(name="PATH", help="The directory to generate a layout for.")]
+    path   : PathBuf,
+    /// The name of the toplevel struct.
+    #[clap(short, long, default_value="RootDir", help="The name of the struct that describes the given directory.")]
+    name   : String,
+    /// Where to write the output to.
+    #[clap(short, long, help="If given, writes the generated code to the given file instead of stdout.")]
+    output : Option<PathBuf>,
+    /// Whether to include hidden entries.
+    #[clap(long, help="If given, also includes hidden files and directories (i.e., those starting with a dot).")]
+    hidden : bool,
+}
+
+
+
+
+
+/***** ENTRYPOINT *****/
+fn main() {
+    // Parse the CLI
+    let args: Arguments = Arguments::parse();
+
+    // Initialize the logger
+    if let Err(err) = HumanLogger::terminal(if args.debug { DebugMode::Debug } else { DebugMode::HumanFriendly }).init() {
+        eprintln!("WARNING: Failed to initialize logger: {err} (no logging enabled for this session)");
+    }
+
+    // Scan the tree
+    info!("Scanning '{}'...", args.path.display());
+    let entries = match Node::scan(&args.path, args.hidden) {
+        Ok(Node::Dir(entries)) => entries,
+        Ok(Node::File)         => { error!("'{}' is not a directory", args.path.display()); std::process::exit(1); },
+        Err(err)               => { error!("Failed to scan '{}': {err}", args.path.display()); std::process::exit(1); },
+    };
+
+    // Generate the code
+    let code: String = Generator::generate(&args.name, &args.path.display().to_string(), &entries);
+
+    // Write it
+    match args.output {
+        Some(output) => if let Err(err) = fs::write(&output, code) {
+            error!("Failed to write generated code to '{}': {err}", output.display());
+            std::process::exit(1);
+        },
+        None => println!("{code}"),
+    }
+}
@@ -0,0 +1,266 @@
+//  TREE.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:55:57
+//  Last edited:
+//    18 Oct 2026, 21:55:57
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines an in-memory representation of a directory tree on disk,
+//!   which we use to generate layouts from.
+// 
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::{self, DirEntry, ReadDir};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+
+/***** ERRORS *****/
+/// Defines errors that may occur when scanning a directory tree.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read a directory.
+    DirRead { path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry within a directory.
+    DirEntryRead { path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to read the metadata of an entry.
+    Metadata { path: PathBuf, err: std::io::Error },
+    /// Failed to resolve a directory to its canonical path.
+    Canonicalize { path: PathBuf, err: std::io::Error },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            DirRead { path, .. }             => write!(f, "Failed to read directory '{}'", path.display()),
+            DirEntryRead { path, entry, .. } => write!(f, "Failed to read entry {} in directory '{}'", entry, path.display()),
+            Metadata { path, .. }            => write!(f, "Failed to read metadata of '{}'", path.display()),
+            Canonicalize { path, .. }        => write!(f, "Failed to canonicalize '{}'", path.display()),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            DirRead { err, .. }      => Some(err),
+            DirEntryRead { err, .. } => Some(err),
+            Metadata { err, .. }     => Some(err),
+            Canonicalize { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single node in a scanned directory tree.
+#[derive(Clone, Debug)]
+pub enum Node {
+    /// It's a file.
+    File,
+    /// It's a directory with the given, named entries.
+    Dir(BTreeMap<String, Node>),
+}
+impl Node {
+    /// Scans the given path on disk.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to scan. Symlinks are followed, except for those that point to a directory we're already in (which would recurse forever).
+    /// - `hidden`: Whether to include hidden entries (i.e., those starting with a dot).
+    /// 
+    /// # Returns
+    /// A new Node that represents whatever is found at `path`.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read any of the directories or entries.
+    #[inline]
+    pub fn scan(path: impl AsRef<Path>, hidden: bool) -> Result<Self, Error> { Self::scan_in(path.as_ref(), hidden, &mut vec![]) }
+
+    /// Scans the given path on disk, skipping any entries that lead back to the directories we're scanning.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to scan.
+    /// - `hidden`: Whether to include hidden entries (i.e., those starting with a dot).
+    /// - `ancestors`: The canonical paths of the directories we're currently scanning (i.e., `path`'s parents).
+    /// 
+    /// # Returns
+    /// A new Node that represents whatever is found at `path`.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read any of the directories or entries.
+    fn scan_in(path: &Path, hidden: bool, ancestors: &mut Vec<PathBuf>) -> Result<Self, Error> {
+        debug!("Scanning '{}'...", path.display());
+
+        // Files are easy
+        match fs::metadata(path) {
+            Ok(md) => if !md.is_dir() { return Ok(Self::File); },
+            Err(err) => { return Err(Error::Metadata { path: path.into(), err }); },
+        }
+
+        // Otherwise, recurse into the directory, remembering that we're in it
+        let canonical: PathBuf = match fs::canonicalize(path) {
+            Ok(canonical) => canonical,
+            Err(err)      => { return Err(Error::Canonicalize { path: path.into(), err }); },
+        };
+        ancestors.push(canonical);
+        let mut entries: BTreeMap<String, Node> = BTreeMap::new();
+        let dir: ReadDir = match fs::read_dir(path) {
+            Ok(dir)  => dir,
+            Err(err) => { return Err(Error::DirRead { path: path.into(), err }); },
+        };
+        for (i, entry) in dir.enumerate() {
+            // Unwrap the entry
+            let entry: DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err)  => { return Err(Error::DirEntryRead { path: path.into(), entry: i, err }); },
+            };
+
+            // Only consider UTF-8 names, since we cannot write anything else in Rust source
+            let name: String = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(name) => {
+                    warn!("Skipping entry '{}' in '{}' because its name is not valid UTF-8", name.to_string_lossy(), path.display());
+                    continue;
+                },
+            };
+            if !hidden && name.starts_with('.') { continue; }
+
+            // Don't follow symlinks back to where we came from
+            let path: PathBuf = entry.path();
+            if entry.file_type().map(|ty| ty.is_symlink()).unwrap_or(false) {
+                if let Ok(target) = fs::canonicalize(&path) {
+                    if ancestors.contains(&target) {
+                        warn!("Skipping symlink '{}' because it points to '{}', which contains it", path.display(), target.display());
+                        continue;
+                    }
+                }
+            }
+
+            // Recurse
+            entries.insert(name, Self::scan_in(&path, hidden, ancestors)?);
+        }
+
+        // Done
+        ancestors.pop();
+        Ok(Self::Dir(entries))
+    }
+
+
+
+    /// Returns a canonical description of the shape of this node, which is equal for two nodes if (and only if) they have the same entries (recursively).
+    /// 
+    /// # Returns
+    /// A [`String`] describing the shape of this node.
+    pub fn shape(&self) -> String {
+        match self {
+            Self::File => "f".into(),
+            Self::Dir(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(name, node)| format!("{name:?}:{}", node.shape())).collect();
+                format!("d{{{}}}", entries.join(","))
+            },
+        }
+    }
+}
+
+/// Returns the entries of a nested directory if all (and at least two) nested directories in the given directory have the same, non-trivial shape.
+/// 
+/// This is what makes them a candidate for a [`directories::std::Dynamic`] field.
+/// 
+/// # Arguments
+/// - `entries`: The entries of the directory to examine.
+/// 
+/// # Returns
+/// The entries of one of the nested directories if they are repeated, or [`None`] otherwise.
+pub fn repeated(entries: &BTreeMap<String, Node>) -> Option<&BTreeMap<String, Node>> {
+    // Collect the nested directories
+    let dirs: Vec<&Node> = entries.values().filter(|n| matches!(n, Node::Dir(_))).collect();
+    if dirs.len() < 2 { return None; }
+
+    // Assert they all have the same shape, and that they are not empty (in which case there is nothing to derive)
+    let shape: String = dirs[0].shape();
+    if dirs.iter().skip(1).any(|n| n.shape() != shape) { return None; }
+    match dirs[0] {
+        Node::Dir(entries) if !entries.is_empty() => Some(entries),
+        _ => None,
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-gen-test-tree-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Shorthand for a directory node with the given entries.
+    fn dir<const N: usize>(entries: [(&str, Node); N]) -> Node { Node::Dir(entries.into_iter().map(|(name, node)| (name.to_string(), node)).collect()) }
+
+
+    #[test]
+    fn test_scan() {
+        let root: PathBuf = scratch("scan");
+        fs::create_dir_all(root.join("src/bin")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join(".hidden"), "").unwrap();
+
+        assert_eq!(Node::scan(&root, false).unwrap().shape(), dir([("src", dir([("bin", dir([])), ("main.rs", Node::File)]))]).shape());
+        assert_eq!(Node::scan(&root, true).unwrap().shape(), dir([(".hidden", Node::File), ("src", dir([("bin", dir([])), ("main.rs", Node::File)]))]).shape());
+        assert!(matches!(Node::scan(root.join("missing"), false), Err(Error::Metadata { .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_symlink_cycle() {
+        use std::os::unix::fs::symlink;
+
+        // Links to a parent are skipped, but other links are still followed
+        let root: PathBuf = scratch("cycle");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("c")).unwrap();
+        fs::write(root.join("c/file.txt"), "").unwrap();
+        symlink(&root, root.join("a/b/up")).unwrap();
+        symlink(root.join("a"), root.join("a/self")).unwrap();
+        symlink(root.join("c"), root.join("a/c")).unwrap();
+        assert_eq!(
+            Node::scan(&root, false).unwrap().shape(),
+            dir([("a", dir([("b", dir([])), ("c", dir([("file.txt", Node::File)]))])), ("c", dir([("file.txt", Node::File)]))]).shape()
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_repeated() {
+        let case = || dir([("input.txt", Node::File), ("output.txt", Node::File)]);
+        let Node::Dir(cases) = dir([("1", case()), ("2", case()), ("README.md", Node::File)]) else { unreachable!() };
+        assert_eq!(repeated(&cases).map(|entries| entries.len()), Some(2));
+
+        // Different shapes, a single directory or empty directories aren't repeated
+        let Node::Dir(cases) = dir([("1", case()), ("2", dir([("input.txt", Node::File)]))]) else { unreachable!() };
+        assert!(repeated(&cases).is_none());
+        let Node::Dir(cases) = dir([("1", case())]) else { unreachable!() };
+        assert!(repeated(&cases).is_none());
+        let Node::Dir(cases) = dir([("1", dir([])), ("2", dir([]))]) else { unreachable!() };
+        assert!(repeated(&cases).is_none());
+    }
+}
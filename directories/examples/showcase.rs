@@ -4,7 +4,7 @@
 //  Created:
 //    20 Apr 2023, 19:08:02
 //  Last edited:
-//    25 Jun 2023, 12:04:14
+//    18 Oct 2026, 22:33:22
 //  Auto updated?
 //    Yes
 // 
@@ -12,7 +12,7 @@
 //!   Shows a few based aspects from the library.
 // 
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use clap::Parser;
@@ -20,7 +20,7 @@ use humanlog::{DebugMode, HumanLogger};
 use log::error;
 
 use directories::{Directory, DirectoryExt as _};
-use directories::std::Dynamic;
+use directories::std::SortedDynamic;
 
 
 /***** DIRECTORIES *****/
@@ -61,10 +61,10 @@ struct TestCasesDir {
     hardcoded_exe : PathBuf,
     /// A variable directory, which matches any nested file/folder
     #[dir(flatten)]
-    test_cases        : HashMap<PathBuf, TestCaseDir>,
+    test_cases        : BTreeMap<PathBuf, TestCaseDir>,
     /// A variable directory, which matches anything only of the given shape
     #[dir(flatten)]
-    test_cases_strict : Dynamic<TestCaseDir>,
+    test_cases_strict : SortedDynamic<TestCaseDir>,
 //     /// A variable list of files, which only matches files
 //     #[file(any)]
 //     test_files    : Vec<PathBuf>,
@@ -0,0 +1,334 @@
+//  ATOMIC.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:21:08
+//  Last edited:
+//    18 Oct 2026, 22:21:08
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements atomic writes to the paths in a layout, such that
+//!   readers (or a process that is killed halfway) never observe a
+//!   partially written file or directory.
+//! 
+//!   Import [`AtomicExt`] to use it on any [`Path`] or [`PathBuf`], e.g.,
+//!   the `#[file]` fields of a layout:
+//!   ```rust,ignore
+//!   use directories::atomic::AtomicExt as _;
+//! 
+//!   layout.config.write_atomic(b"key = \"value\"\n")?;
+//!   ```
+// 
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::directory::Error;
+
+
+/***** CONSTANTS *****/
+/// A counter to make temporary names unique within this process.
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Generates a path for a temporary sibling of the given path.
+/// 
+/// # Arguments
+/// - `path`: The path to generate a sibling for.
+/// - `what`: A short tag describing what the sibling is for (e.g., `tmp`).
+/// 
+/// # Returns
+/// A hidden path in the same directory as `path` that is unique within this process.
+fn sibling(path: &Path, what: &str) -> PathBuf {
+    let name: String = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let n: usize = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{name}.{what}.{}.{n}", std::process::id()))
+}
+
+/// Returns the directory that contains the given path.
+/// 
+/// # Arguments
+/// - `path`: The path to find the parent of.
+/// 
+/// # Returns
+/// The parent of the path, or `.` if it has none.
+fn parent(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Resolves the given path if it is a symlink, following any chain of symlinks until something that isn't one.
+/// 
+/// Unlike [`fs::canonicalize()`], this also works for symlinks of which the target does not exist (yet), and leaves the parent directories as-is.
+/// 
+/// # Arguments
+/// - `path`: The path to resolve.
+/// 
+/// # Returns
+/// The path that the symlink(s) eventually point to, or `path` itself if it is not a symlink.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the symlinks, or if they form a loop.
+fn resolve_link(path: &Path) -> Result<PathBuf, Error> {
+    let mut path: PathBuf = path.into();
+    // Mirror the limit that Linux puts on the number of symlinks followed
+    for _ in 0..40 {
+        match fs::symlink_metadata(&path) {
+            Ok(md) if md.file_type().is_symlink() => {},
+            _ => { return Ok(path); },
+        }
+        let target: PathBuf = match fs::read_link(&path) {
+            Ok(target) => target,
+            Err(err)   => { return Err(Error::LinkRead { path, err }); },
+        };
+        path = parent(&path).join(target);
+    }
+    Err(Error::LinkRead { path, err: std::io::Error::other("Too many levels of symbolic links") })
+}
+
+/// Flushes the entries of the given directory to disk, such that renames in it are durable.
+/// 
+/// This does nothing on non-Unix platforms, where directories cannot be opened as files.
+/// 
+/// # Arguments
+/// - `dir`: The directory to flush.
+/// 
+/// # Errors
+/// This function errors if we failed to open or flush the directory.
+fn sync_dir(dir: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    if let Err(err) = File::open(dir).and_then(|handle| handle.sync_all()) { return Err(Error::FileWrite { path: dir.into(), err }); }
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Renames the given path, cleaning up the source if it fails.
+/// 
+/// # Arguments
+/// - `from`: The path to rename.
+/// - `to`: The path to rename it to.
+/// 
+/// # Errors
+/// This function errors if we failed to rename the path.
+fn rename(from: &Path, to: &Path) -> Result<(), Error> {
+    if let Err(err) = fs::rename(from, to) {
+        let _ = if from.is_dir() { fs::remove_dir_all(from) } else { fs::remove_file(from) };
+        return Err(Error::Rename { from: from.into(), to: to.into(), err });
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Extends paths with atomic alternatives to writing files and replacing directories.
+pub trait AtomicExt {
+    /// Writes the given bytes to the file at this path, atomically replacing it if it exists.
+    /// 
+    /// The bytes are written to a temporary file in the same directory first, which is flushed to disk and then renamed into place. Readers thus either see the old or the new contents, even if the process is killed while writing. If the file existed, its permissions are kept.
+    /// 
+    /// If this path is a symlink, the file it points to is replaced instead, such that the symlink is kept.
+    /// 
+    /// # Arguments
+    /// - `bytes`: The new contents of the file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to resolve the path, failed to write or flush the temporary file, or failed to rename it into place. In that case, the original file is left untouched.
+    fn write_atomic(&self, bytes: impl AsRef<[u8]>) -> Result<(), Error>;
+
+    /// Atomically replaces the directory at this path with a new one.
+    /// 
+    /// The new directory is first created next to the old one, and populated by the given closure (e.g., by initializing a layout in it and calling [`DirectoryExt::ensure()`](crate::DirectoryExt::ensure())). Only if that succeeds, the old directory is swapped out for the new one and removed. If the closure fails, the old directory is left untouched.
+    /// 
+    /// Note that the swap consists of two renames, so the path briefly does not exist in between. It never refers to a partially populated directory, though.
+    /// 
+    /// Once the new directory is in place, failing to remove the old one is not an error, since the replacement itself succeeded. The old directory is then left behind as a hidden sibling (and logged if the `log` feature is enabled).
+    /// 
+    /// # Arguments
+    /// - `fill`: A closure that populates the new directory, given its (temporary) path.
+    /// 
+    /// # Errors
+    /// This function errors if `fill` errors, or if we failed to create or swap the directories. In that case, the old directory is left untouched.
+    fn replace_dir_atomic<E: From<Error>>(&self, fill: impl FnOnce(&Path) -> Result<(), E>) -> Result<(), E>;
+}
+impl AtomicExt for Path {
+    fn write_atomic(&self, bytes: impl AsRef<[u8]>) -> Result<(), Error> {
+        // Write to what this path points to, so we don't replace a symlink with a file
+        let path: PathBuf = resolve_link(self)?;
+        let tmp: PathBuf = sibling(&path, "tmp");
+
+        // Write the contents to the temporary file
+        let mut handle: File = match OpenOptions::new().write(true).create_new(true).open(&tmp) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileWrite { path: tmp, err }); },
+        };
+        let res: std::io::Result<()> = (|| {
+            if let Ok(md) = fs::metadata(&path) { handle.set_permissions(md.permissions())?; }
+            handle.write_all(bytes.as_ref())?;
+            handle.sync_all()
+        })();
+        drop(handle);
+        if let Err(err) = res {
+            let _ = fs::remove_file(&tmp);
+            return Err(Error::FileWrite { path: tmp, err });
+        }
+
+        // Move it into place
+        rename(&tmp, &path)?;
+        sync_dir(parent(&path))
+    }
+
+    fn replace_dir_atomic<E: From<Error>>(&self, fill: impl FnOnce(&Path) -> Result<(), E>) -> Result<(), E> {
+        let tmp: PathBuf = sibling(self, "tmp");
+
+        // Populate the new directory
+        if let Err(err) = fs::create_dir(&tmp) { return Err(Error::DirCreate { path: tmp, err }.into()); }
+        if let Err(err) = fill(&tmp) {
+            let _ = fs::remove_dir_all(&tmp);
+            return Err(err);
+        }
+        sync_dir(&tmp)?;
+
+        // Swap it with the old one, if any
+        if fs::symlink_metadata(self).is_ok() {
+            let old: PathBuf = sibling(self, "old");
+            if let Err(err) = fs::rename(self, &old) {
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(Error::Rename { from: self.into(), to: old, err }.into());
+            }
+            if let Err(err) = fs::rename(&tmp, self) {
+                // Put the old one back
+                let _ = fs::rename(&old, self);
+                let _ = fs::remove_dir_all(&tmp);
+                return Err(Error::Rename { from: tmp, to: self.into(), err }.into());
+            }
+            sync_dir(parent(self))?;
+
+            // The swap succeeded, so failing to clean up the old one doesn't undo that
+            if let Err(_err) = fs::remove_dir_all(&old) {
+                #[cfg(feature = "log")]
+                log::warn!("Failed to remove old directory '{}' after replacing '{}': {_err}", old.display(), self.display());
+            }
+        } else {
+            rename(&tmp, self)?;
+            sync_dir(parent(self))?;
+        }
+        Ok(())
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-test-atomic-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Returns the names of all entries in the given directory, sorted.
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        names.sort();
+        names
+    }
+
+
+    #[test]
+    fn test_write_atomic() {
+        let root: PathBuf = scratch("write");
+        let config: PathBuf = root.join("config.toml");
+
+        // It creates and replaces files, without leaving temporary files behind
+        config.write_atomic(b"old").unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "old");
+        config.write_atomic("new").unwrap();
+        assert_eq!(fs::read_to_string(&config).unwrap(), "new");
+        assert_eq!(entries(&root), ["config.toml"]);
+
+        // Failing to write leaves nothing behind either
+        assert!(matches!(root.join("missing/config.toml").write_atomic("new"), Err(Error::FileWrite { .. })));
+        assert_eq!(entries(&root), ["config.toml"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_unix() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let root: PathBuf = scratch("write-unix");
+        let config: PathBuf = root.join("config.toml");
+        fs::write(&config, "old").unwrap();
+        fs::set_permissions(&config, fs::Permissions::from_mode(0o600)).unwrap();
+
+        // The permissions of the original are kept
+        config.write_atomic("new").unwrap();
+        assert_eq!(fs::metadata(&config).unwrap().permissions().mode() & 0o7777, 0o600);
+
+        // Symlinks are kept too, replacing what they point to instead
+        std::os::unix::fs::symlink("config.toml", root.join("link")).unwrap();
+        std::os::unix::fs::symlink("link", root.join("chain")).unwrap();
+        root.join("chain").write_atomic("newer").unwrap();
+        assert!(fs::symlink_metadata(root.join("chain")).unwrap().file_type().is_symlink());
+        assert!(fs::symlink_metadata(root.join("link")).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&config).unwrap(), "newer");
+        assert_eq!(entries(&root), ["chain", "config.toml", "link"]);
+
+        // Unless they form a loop
+        std::os::unix::fs::symlink("loop", root.join("loop")).unwrap();
+        assert!(matches!(root.join("loop").write_atomic("new"), Err(Error::LinkRead { .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_replace_dir_atomic() {
+        let root: PathBuf = scratch("replace");
+        let data: PathBuf = root.join("data");
+
+        // It creates a directory that doesn't exist yet
+        data.replace_dir_atomic(|tmp: &Path| -> Result<(), Error> {
+            fs::write(tmp.join("v1"), "").map_err(|err| Error::FileWrite { path: tmp.join("v1"), err })
+        }).unwrap();
+        assert_eq!(entries(&data), ["v1"]);
+
+        // And replaces one that does, with the old contents gone
+        data.replace_dir_atomic(|tmp: &Path| -> Result<(), Error> {
+            assert!(data.join("v1").exists());
+            fs::write(tmp.join("v2"), "").map_err(|err| Error::FileWrite { path: tmp.join("v2"), err })
+        }).unwrap();
+        assert_eq!(entries(&data), ["v2"]);
+        assert_eq!(entries(&root), ["data"]);
+
+        // If populating fails, the old one is left untouched and the new one cleaned up
+        let res: Result<(), Error> = data.replace_dir_atomic(|tmp: &Path| {
+            fs::write(tmp.join("v3"), "").unwrap();
+            Err(Error::FileWrite { path: tmp.join("v3"), err: std::io::Error::other("Oops") })
+        });
+        assert!(matches!(res, Err(Error::FileWrite { .. })));
+        assert_eq!(entries(&data), ["v2"]);
+        assert_eq!(entries(&root), ["data"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,388 @@
+//  CAP.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:17:38
+//  Last edited:
+//    18 Oct 2026, 22:39:27
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines [`Directory`] types that hold handles opened through a
+//!   [`cap_std`] capability, for use with [`Directory::try_init_cap()`].
+//! 
+//!   When a layout is initialized with [`Directory::try_init_cap()`], all
+//!   paths in it are relative to the capability. [`PathBuf`] fields can
+//!   then be opened through the capability later, while [`DirHandle`] and
+//!   [`FileHandle`] fields are opened during initialization. Either way,
+//!   nothing can refer to outside of the capability. Similarly,
+//!   [`Opened<T>`](crate::std::Opened) opens a directory and initializes
+//!   `T` with it as its capability.
+//! 
+//!   Note that [`DirectoryExt::exists()`](crate::DirectoryExt::exists()),
+//!   [`DirectoryExt::validate()`](crate::DirectoryExt::validate()) and
+//!   [`DirectoryExt::ensure()`](crate::DirectoryExt::ensure()) operate on
+//!   the ambient filesystem, and would thus interpret these relative paths
+//!   relative to the current working directory. Use their `_cap`
+//!   counterparts (e.g.,
+//!   [`DirectoryExt::validate_cap()`](crate::DirectoryExt::validate_cap()))
+//!   instead.
+// 
+
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use cap_std::ambient_authority;
+use cap_std::fs::{Dir, DirEntry, File, Metadata, OpenOptions, ReadDir};
+
+use crate::directory::{Directory, DirectoryExt, Error, FieldSpec, Fs, InitOptions, Stat, SymlinkPolicy};
+use crate::schema::{EntryKind, EntrySchema, Schema};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns a path relative to a capability that can be given to [`cap_std`], i.e., where the root of the capability is `.` instead of empty.
+/// 
+/// # Arguments
+/// - `path`: The path relative to the capability.
+/// 
+/// # Returns
+/// The given path, or `.` if it's empty.
+#[inline]
+pub(crate) fn rel(path: &Path) -> &Path { if path.as_os_str().is_empty() { Path::new(".") } else { path } }
+
+/// Lists the entries of a directory in a capability to initialize dynamic fields with.
+/// 
+/// This is the capability-based counterpart to scanning on the ambient filesystem.
+/// 
+/// # Arguments
+/// - `cap`: The [`Dir`] that is the capability.
+/// - `base`: The directory to scan, relative to the capability.
+/// - `opts`: The [`InitOptions`] that determine what to do with symlinks.
+/// 
+/// # Returns
+/// The paths of the entries in the directory (relative to the capability), or an empty list if it does not exist.
+/// 
+/// # Errors
+/// This function errors if we failed to read the directory, or if we found a symlink while the policy is [`SymlinkPolicy::Error`].
+pub(crate) fn scan(cap: &Dir, base: &Path, opts: &InitOptions) -> Result<Vec<PathBuf>, Error> {
+    let entries: ReadDir = match cap.read_dir(rel(base)) {
+        Ok(entries) => entries,
+        Err(err) => {
+            // If we failed to read the directory because it does not exist, we conclude no files exist either
+            if err.kind() == ErrorKind::NotFound { return Ok(vec![]); }
+            // Otherwise, error hard
+            return Err(Error::DirRead { path: base.into(), err });
+        },
+    };
+    let mut paths: Vec<PathBuf> = vec![];
+    for (i, entry) in entries.enumerate() {
+        // Unwrap the entry
+        let entry: DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Error::DirEntryRead { path: base.into(), entry: i, err }); },
+        };
+
+        // Apply the glob patterns and regular expression
+        if !opts.name_matches(base, &entry.file_name()) { continue; }
+        let path: PathBuf = base.join(entry.file_name());
+
+        // Apply the symlink policy
+        if opts.symlinks != SymlinkPolicy::Follow {
+            let is_link: bool = match entry.file_type() {
+                Ok(ty)   => ty.is_symlink(),
+                Err(err) => { return Err(Error::DirEntryRead { path: base.into(), entry: i, err }); },
+            };
+            if is_link {
+                match opts.symlinks {
+                    SymlinkPolicy::Skip   => { continue; },
+                    SymlinkPolicy::Error  => { return Err(Error::Symlink { path }); },
+                    SymlinkPolicy::Follow => unreachable!(),
+                }
+            }
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+// Paths in the capability are relative to it, like the paths in layouts initialized with [`Directory::try_init_cap()`].
+impl Fs for Dir {
+    fn stat(&self, path: &Path, follow: bool) -> Option<Stat> {
+        let md: Metadata = if follow { self.metadata(rel(path)) } else { self.symlink_metadata(rel(path)) }.ok()?;
+        #[cfg(unix)]
+        let mode: u32 = {
+            use cap_std::fs::PermissionsExt as _;
+            md.permissions().mode()
+        };
+        #[cfg(not(unix))]
+        let mode: u32 = 0;
+        Some(Stat { is_file: md.is_file(), is_dir: md.is_dir(), is_symlink: md.file_type().is_symlink(), mode })
+    }
+
+    #[inline]
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf> { self.read_link_contents(rel(path)) }
+
+    #[inline]
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf> { self.canonicalize(rel(path)) }
+
+    #[inline]
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> { self.create_dir_all(rel(path)) }
+
+    fn create_new(&self, path: &Path, mode: Option<u32>) -> std::io::Result<Box<dyn Write>> {
+        let mut opts: OpenOptions = OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use cap_std::fs::OpenOptionsExt as _;
+            opts.mode(mode & 0o777);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        Ok(Box::new(self.open_with(rel(path), &opts)?))
+    }
+
+    #[cfg(unix)]
+    fn set_mode(&self, path: &Path, mode: u32) -> std::io::Result<()> {
+        use cap_std::fs::{Permissions, PermissionsExt as _};
+        self.set_permissions(rel(path), Permissions::from_mode(mode))
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a directory that is opened as a [`Dir`] handle when it is initialized.
+/// 
+/// If initialized with [`Directory::try_init_cap()`], it is opened through the capability; otherwise, it is opened on the ambient filesystem.
+#[derive(Debug)]
+pub struct DirHandle {
+    /// The path of the directory (relative to the capability, if any).
+    path   : PathBuf,
+    /// The opened directory, if it existed.
+    handle : Option<Dir>,
+}
+impl DirHandle {
+    /// Returns the path of the directory, which is relative to the capability if it was initialized with one.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the opened directory.
+    /// 
+    /// # Returns
+    /// The [`Dir`] handle, or [`None`] if it did not exist (or was not a directory) when this DirHandle was initialized.
+    #[inline]
+    pub fn handle(&self) -> Option<&Dir> { self.handle.as_ref() }
+
+    /// Returns the opened directory by ownership.
+    /// 
+    /// # Returns
+    /// The [`Dir`] handle, or [`None`] if it did not exist (or was not a directory) when this DirHandle was initialized.
+    #[inline]
+    pub fn into_handle(self) -> Option<Dir> { self.handle }
+}
+
+impl Directory for DirHandle {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let path: PathBuf = base.into();
+        let res: std::io::Result<Dir> = match &opts.cap {
+            Some(cap) => cap.open_dir(rel(&path)),
+            None      => Dir::open_ambient_dir(&path, ambient_authority()),
+        };
+        let handle: Option<Dir> = match res {
+            Ok(handle) => Some(handle),
+            // It doesn't exist or isn't a directory
+            Err(err) if err.kind() == ErrorKind::NotFound || err.kind() == ErrorKind::NotADirectory => None,
+            Err(err) => { return Err(Error::Open { path, err }); },
+        };
+        Ok(Self { path, handle })
+    }
+}
+impl DirectoryExt for DirHandle {
+    #[inline]
+    fn exists(&self) -> bool { self.handle.is_some() }
+
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> { visit(&self.path, spec) }
+}
+impl Schema for DirHandle {
+    #[inline]
+    fn schema() -> EntrySchema { EntrySchema { kind: EntryKind::Dir, ..Default::default() } }
+}
+
+impl AsRef<Path> for DirHandle {
+    #[inline]
+    fn as_ref(&self) -> &Path { &self.path }
+}
+
+
+
+/// Defines a file that is opened (read-only) as a [`File`] handle when it is initialized.
+/// 
+/// If initialized with [`Directory::try_init_cap()`], it is opened through the capability; otherwise, it is opened on the ambient filesystem.
+#[derive(Debug)]
+pub struct FileHandle {
+    /// The path of the file (relative to the capability, if any).
+    path   : PathBuf,
+    /// The opened file, if it existed.
+    handle : Option<File>,
+}
+impl FileHandle {
+    /// Returns the path of the file, which is relative to the capability if it was initialized with one.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the opened file.
+    /// 
+    /// # Returns
+    /// The [`File`] handle, or [`None`] if it did not exist (or was not a file) when this FileHandle was initialized.
+    #[inline]
+    pub fn handle(&self) -> Option<&File> { self.handle.as_ref() }
+
+    /// Returns the opened file by ownership.
+    /// 
+    /// # Returns
+    /// The [`File`] handle, or [`None`] if it did not exist (or was not a file) when this FileHandle was initialized.
+    #[inline]
+    pub fn into_handle(self) -> Option<File> { self.handle }
+}
+
+impl Directory for FileHandle {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let path: PathBuf = base.into();
+        let res: std::io::Result<File> = match &opts.cap {
+            Some(cap) => cap.open(rel(&path)),
+            None      => File::open_ambient(&path, ambient_authority()),
+        };
+        let handle: Option<File> = match res {
+            Ok(handle) => Some(handle),
+            // It doesn't exist
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => { return Err(Error::Open { path, err }); },
+        };
+
+        // Opening a directory may succeed, so filter those out
+        let handle: Option<File> = match handle.map(|h| h.metadata().map(|md| (md.is_file(), h))).transpose() {
+            Ok(Some((true, handle))) => Some(handle),
+            Ok(_)                    => None,
+            Err(err)                 => { return Err(Error::Open { path, err }); },
+        };
+        Ok(Self { path, handle })
+    }
+}
+impl DirectoryExt for FileHandle {
+    #[inline]
+    fn exists(&self) -> bool { self.handle.is_some() }
+
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> { visit(&self.path, spec) }
+}
+impl Schema for FileHandle {
+    #[inline]
+    fn schema() -> EntrySchema { EntrySchema { kind: EntryKind::File, ..Default::default() } }
+}
+
+impl AsRef<Path> for FileHandle {
+    #[inline]
+    fn as_ref(&self) -> &Path { &self.path }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::directory::Problem;
+    use crate::std::{Dynamic, Symlink};
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-test-cap-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_init_cap() {
+        #[derive(crate::Directory)]
+        struct Layout {
+            #[dir]
+            plugins: Dynamic<PathBuf>,
+            #[file]
+            config_json: PathBuf,
+        }
+
+        // Paths are relative to the capability, and dynamic fields are scanned through it rather than the current directory
+        let root: PathBuf = scratch("init");
+        fs::create_dir_all(root.join("plugins/a")).unwrap();
+        fs::create_dir_all(root.join("plugins/b")).unwrap();
+        let cap: Dir = Dir::open_ambient_dir(&root, ambient_authority()).unwrap();
+        let layout: Layout = Layout::try_init_cap(cap.try_clone().unwrap()).unwrap();
+        assert_eq!(layout.config_json, Path::new("config.json"));
+        let mut plugins: Vec<&PathBuf> = layout.plugins.keys().collect();
+        plugins.sort();
+        assert_eq!(plugins, [Path::new("plugins/a"), Path::new("plugins/b")]);
+
+        // Checking and creating goes through it too
+        assert!(!layout.exists_cap(&cap));
+        assert!(matches!(layout.validate_cap(&cap).as_slice(), [Problem::Missing { path, .. }] if path == Path::new("config.json")));
+        layout.ensure_cap(&cap).unwrap();
+        assert!(root.join("config.json").is_file());
+        assert!(layout.exists_cap(&cap));
+        assert!(layout.validate_cap(&cap).is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_init_cap_symlink() {
+        let root: PathBuf = scratch("symlink");
+        std::os::unix::fs::symlink("target", root.join("link")).unwrap();
+        let cap: Dir = Dir::open_ambient_dir(&root, ambient_authority()).unwrap();
+        let opts: InitOptions = InitOptions { cap: Some(Arc::new(cap)), ..Default::default() };
+        assert_eq!(Symlink::try_init_with("link", &opts).unwrap().target(), Some(Path::new("target")));
+        assert_eq!(Symlink::try_init_with("missing", &opts).unwrap().target(), None);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not support being initialized relative to a capability")]
+    fn test_init_cap_unsupported() {
+        /// Only implements [`Directory::try_init()`], and would thus resolve against the current directory.
+        struct Cwd(#[allow(dead_code)] PathBuf);
+        impl Directory for Cwd {
+            type Error = Error;
+
+            #[inline]
+            fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Ok(Self(base.into())) }
+        }
+
+        let _ = Cwd::try_init_cap(Dir::open_ambient_dir(std::env::temp_dir(), ambient_authority()).unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+//  CAPTURES.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:39:27
+//  Last edited:
+//    18 Oct 2026, 22:39:27
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements the groups captured by `#[dir(regex = ...)]` from the
+//!   names of dynamic entries, as exposed by
+//!   [`Matched<T, K>`](crate::std::Matched).
+//! 
+//!   The captures may be parsed into a typed key by implementing
+//!   [`FromCaptures`], e.g.:
+//!   ```rust,ignore
+//!   struct RunKey {
+//!       id   : u32,
+//!       date : String,
+//!   }
+//!   impl FromCaptures for RunKey {
+//!       fn from_captures(captures: &Captures) -> Result<Self, CaptureError> {
+//!           Ok(Self { id: captures.parse("id")?, date: captures.parse("date")? })
+//!       }
+//!   }
+//! 
+//!   #[derive(Directory)]
+//!   struct Workspace {
+//!       #[dir(path = "runs", regex = r"^run-(?P<id>\d+)-(?P<date>\d{8})$")]
+//!       runs : Matched<RunDir, RunKey>,
+//!   }
+//!   ```
+// 
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+
+pub use regex::Regex;
+
+
+/***** ERRORS *****/
+/// Defines errors that may occur when converting [`Captures`] to a typed key.
+#[derive(Debug)]
+pub enum CaptureError {
+    /// The given group did not capture anything (or does not exist).
+    Missing { group: String },
+    /// The given group captured something that could not be parsed.
+    Parse { group: String, value: String, err: Box<dyn Error + Send + Sync> },
+}
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CaptureError::*;
+        match self {
+            Missing { group }          => write!(f, "Group '{group}' did not capture anything"),
+            Parse { group, value, .. } => write!(f, "Failed to parse '{value}' captured by group '{group}'"),
+        }
+    }
+}
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use CaptureError::*;
+        match self {
+            Missing { .. }    => None,
+            Parse { err, .. } => Some(&**err),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the groups captured by a regular expression from the name of a dynamic entry.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Captures {
+    /// The captures by index, where the 0th is the whole match.
+    indexed : Vec<Option<String>>,
+    /// The captures of named groups.
+    named   : BTreeMap<String, String>,
+}
+impl Captures {
+    /// Constructor for the Captures that matches the given regular expression against the given name.
+    /// 
+    /// # Arguments
+    /// - `regex`: The [`Regex`] to match.
+    /// - `name`: The name of the entry to match it against.
+    /// 
+    /// # Returns
+    /// A new Captures instance, or [`None`] if the regular expression does not match the name.
+    pub fn new(regex: &Regex, name: &str) -> Option<Self> {
+        let captures: regex::Captures = regex.captures(name)?;
+        let indexed: Vec<Option<String>> = captures.iter().map(|c| c.map(|c| c.as_str().into())).collect();
+        let named: BTreeMap<String, String> = regex.capture_names().flatten().filter_map(|group| Some((group.into(), captures.name(group)?.as_str().into()))).collect();
+        Some(Self { indexed, named })
+    }
+
+    /// Constructor for the Captures that captures only the whole name, e.g., for when no regular expression is given.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the entry.
+    /// 
+    /// # Returns
+    /// A new Captures instance with only the 0th group.
+    #[inline]
+    pub fn whole(name: impl Into<String>) -> Self { Self { indexed: vec![Some(name.into())], named: BTreeMap::new() } }
+
+
+
+    /// Returns what the whole regular expression matched.
+    #[inline]
+    pub fn as_str(&self) -> &str { self.indexed.first().and_then(Option::as_deref).unwrap_or_default() }
+
+    /// Returns what the group with the given index captured.
+    /// 
+    /// # Arguments
+    /// - `index`: The index of the group, where `0` is the whole match.
+    /// 
+    /// # Returns
+    /// The captured string, or [`None`] if the group did not capture anything (or does not exist).
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&str> { self.indexed.get(index).and_then(Option::as_deref) }
+
+    /// Returns what the named group captured.
+    /// 
+    /// # Arguments
+    /// - `group`: The name of the group (e.g., `id` for `(?P<id>...)`).
+    /// 
+    /// # Returns
+    /// The captured string, or [`None`] if the group did not capture anything (or does not exist).
+    #[inline]
+    pub fn name(&self, group: &str) -> Option<&str> { self.named.get(group).map(String::as_str) }
+
+    /// Parses what the named group captured with [`FromStr`].
+    /// 
+    /// # Generic arguments
+    /// - `V`: The type to parse to.
+    /// 
+    /// # Arguments
+    /// - `group`: The name of the group (e.g., `id` for `(?P<id>...)`).
+    /// 
+    /// # Returns
+    /// The parsed value.
+    /// 
+    /// # Errors
+    /// This function errors if the group did not capture anything, or if we failed to parse what it did.
+    pub fn parse<V: FromStr>(&self, group: &str) -> Result<V, CaptureError> where V::Err: Error + Send + Sync + 'static {
+        let value: &str = self.name(group).ok_or_else(|| CaptureError::Missing { group: group.into() })?;
+        value.parse().map_err(|err| CaptureError::Parse { group: group.into(), value: value.into(), err: Box::new(err) })
+    }
+
+    /// Returns an iterator over the names and captures of all named groups that captured something, sorted by name.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> { self.named.iter().map(|(g, v)| (g.as_str(), v.as_str())) }
+}
+
+
+
+/// Converts the [`Captures`] of a dynamic entry to a typed key, as used by [`Matched<T, K>`](crate::std::Matched).
+pub trait FromCaptures: Sized {
+    /// Converts the given captures to Self.
+    /// 
+    /// # Arguments
+    /// - `captures`: The [`Captures`] of the entry.
+    /// 
+    /// # Returns
+    /// A new instance of Self.
+    /// 
+    /// # Errors
+    /// This function should error if the captures cannot be converted, typically because [`Captures::parse()`] failed.
+    fn from_captures(captures: &Captures) -> Result<Self, CaptureError>;
+}
+
+// Default implementation for the [`Captures`] themselves, which simply copies them.
+impl FromCaptures for Captures {
+    #[inline]
+    fn from_captures(captures: &Captures) -> Result<Self, CaptureError> { Ok(captures.clone()) }
+}
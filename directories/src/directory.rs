@@ -4,7 +4,7 @@
 //  Created:
 //    21 Apr 2023, 09:04:29
 //  Last edited:
-//    25 Jun 2023, 12:05:55
+//    18 Oct 2026, 22:45:10
 //  Auto updated?
 //    Yes
 // 
@@ -13,23 +13,97 @@
 //!   layout.
 // 
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::error;
+use std::ffi::OsStr;
 use std::fmt::{Display, Formatter, Result as FResult};
-use std::fs::{self, DirEntry, ReadDir};
-use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "lock")]
+use std::fs::{File, TryLockError};
+use std::fs::{self, DirEntry, OpenOptions, ReadDir};
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "cap-std")]
+use std::sync::Arc;
+#[cfg(feature = "regex")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+
+#[cfg(feature = "regex")]
+use crate::captures::{CaptureError, Regex};
+use crate::glob::Patterns;
+use crate::schema::EntryKind;
+use crate::template::Context;
 
 
 /***** ERRORS *****/
 /// Defines errors that may occur when generating directories or when initializing dynamic types (such as [`HashMap<PathBuf, T>`]).
+/// 
+/// Some variants only exist if the feature that produces them is enabled, and more may be added later. As such, the enum is non-exhaustive.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     // Dynamic initialization
     /// Failed to read a directory.
     DirRead { path: PathBuf, err: std::io::Error },
     /// Failed to read an entry within a directory.
     DirEntryRead { path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to read a file.
+    FileRead { path: PathBuf, err: std::io::Error },
+    /// Found a symlink while scanning a directory, which is not allowed by the [`SymlinkPolicy`].
+    Symlink { path: PathBuf },
+    /// Found an entry with a name that is not valid UTF-8 while scanning a directory, which is not allowed by the [`NamePolicy`].
+    NonUtf8Name { path: PathBuf },
+    /// Failed to parse the name of a dynamic entry as a key, which is not allowed by the [`KeyPolicy`].
+    Key { path: PathBuf, err: Box<dyn error::Error + Send + Sync> },
+    /// Found two dynamic entries of which the names parse to the same key (e.g., `01` and `1`), which is not allowed by the [`KeyPolicy`].
+    DuplicateKey { path: PathBuf, other: PathBuf },
+    /// Failed to compile the regular expression of a dynamic field.
+    #[cfg(feature = "regex")]
+    Regex { pattern: String, err: regex::Error },
+    /// Failed to convert the groups captured from the name of a dynamic entry.
+    #[cfg(feature = "regex")]
+    Capture { path: PathBuf, err: CaptureError },
+    /// Failed to read the target of a symlink.
+    LinkRead { path: PathBuf, err: std::io::Error },
+    /// Failed to resolve a path to its canonical form.
+    Canonicalize { path: PathBuf, err: std::io::Error },
+    /// A path resolves to outside of the root it's confined to.
+    Escape { path: PathBuf, resolved: PathBuf, root: PathBuf },
+    /// Failed to open a file or directory handle.
+    Open { path: PathBuf, err: std::io::Error },
+
+    // Writing layouts
+    /// Failed to create a directory.
+    DirCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to write a file.
+    FileWrite { path: PathBuf, err: std::io::Error },
+    /// Failed to render the template for a file.
+    Template { path: PathBuf, err: crate::template::Error },
+    /// Failed to set the permissions of a file or directory.
+    Permissions { path: PathBuf, err: std::io::Error },
+    /// Failed to rename a file or directory.
+    Rename { from: PathBuf, to: PathBuf, err: std::io::Error },
+    /// Failed to remove a file or directory.
+    Remove { path: PathBuf, err: std::io::Error },
+
+    // Locking layouts
+    /// Attempted to lock a layout that does not declare a lock file.
+    #[cfg(feature = "lock")]
+    NoLockfile,
+    /// Failed to open or lock a lock file.
+    #[cfg(feature = "lock")]
+    Lock { path: PathBuf, err: std::io::Error },
+
+    // Runtime layouts
+    /// Failed to find a layout specification in a directory.
+    #[cfg(feature = "layout")]
+    SpecNotFound { path: PathBuf },
+    /// Failed to load a layout specification.
+    #[cfg(feature = "layout")]
+    Spec { path: PathBuf, err: crate::layout::Error },
 }
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -37,6 +111,36 @@ impl Display for Error {
         match self {
             DirRead { path, .. }             => write!(f, "Failed to read directory '{}'", path.display()),
             DirEntryRead { path, entry, .. } => write!(f, "Failed to read entry {} in directory '{}'", entry, path.display()),
+            FileRead { path, .. }            => write!(f, "Failed to read file '{}'", path.display()),
+            Symlink { path }                 => write!(f, "Found symlink '{}' while scanning, which is not allowed", path.display()),
+            NonUtf8Name { path }             => write!(f, "Found entry '{}' while scanning, of which the name is not valid UTF-8", path.display()),
+            Key { path, .. }                 => write!(f, "Failed to parse the name of entry '{}' as a key", path.display()),
+            DuplicateKey { path, other }     => write!(f, "Entry '{}' has the same key as entry '{}'", path.display(), other.display()),
+            #[cfg(feature = "regex")]
+            Regex { pattern, .. }            => write!(f, "Failed to compile regular expression '{pattern}'"),
+            #[cfg(feature = "regex")]
+            Capture { path, .. }             => write!(f, "Failed to convert the groups captured from the name of '{}'", path.display()),
+            LinkRead { path, .. }            => write!(f, "Failed to read target of symlink '{}'", path.display()),
+            Canonicalize { path, .. }        => write!(f, "Failed to resolve path '{}'", path.display()),
+            Escape { path, resolved, root }  => write!(f, "Path '{}' resolves to '{}', which is outside of '{}'", path.display(), resolved.display(), root.display()),
+            Open { path, .. }                => write!(f, "Failed to open '{}'", path.display()),
+
+            DirCreate { path, .. }   => write!(f, "Failed to create directory '{}'", path.display()),
+            FileWrite { path, .. }   => write!(f, "Failed to write file '{}'", path.display()),
+            Template { path, .. }    => write!(f, "Failed to render template for file '{}'", path.display()),
+            Permissions { path, .. } => write!(f, "Failed to set permissions of '{}'", path.display()),
+            Rename { from, to, .. }  => write!(f, "Failed to rename '{}' to '{}'", from.display(), to.display()),
+            Remove { path, .. }      => write!(f, "Failed to remove '{}'", path.display()),
+
+            #[cfg(feature = "lock")]
+            NoLockfile       => write!(f, "Layout does not declare a lock file (use '#[directories(lockfile = ...)]')"),
+            #[cfg(feature = "lock")]
+            Lock { path, .. } => write!(f, "Failed to lock '{}'", path.display()),
+
+            #[cfg(feature = "layout")]
+            SpecNotFound { path } => write!(f, "Failed to find layout specification in directory '{}' (expected any of {})", path.display(), crate::layout::SPEC_FILES.iter().map(|f| format!("'{f}'")).collect::<Vec<String>>().join(", ")),
+            #[cfg(feature = "layout")]
+            Spec { path, .. }     => write!(f, "Failed to load layout specification '{}'", path.display()),
         }
     }
 }
@@ -46,6 +150,36 @@ impl error::Error for Error {
         match self {
             DirRead { err, .. }      => Some(err),
             DirEntryRead { err, .. } => Some(err),
+            FileRead { err, .. }     => Some(err),
+            Symlink { .. }           => None,
+            NonUtf8Name { .. }       => None,
+            Key { err, .. }          => Some(&**err),
+            DuplicateKey { .. }      => None,
+            #[cfg(feature = "regex")]
+            Regex { err, .. }        => Some(err),
+            #[cfg(feature = "regex")]
+            Capture { err, .. }      => Some(err),
+            LinkRead { err, .. }     => Some(err),
+            Canonicalize { err, .. } => Some(err),
+            Escape { .. }            => None,
+            Open { err, .. }         => Some(err),
+
+            DirCreate { err, .. }   => Some(err),
+            FileWrite { err, .. }   => Some(err),
+            Template { err, .. }    => Some(err),
+            Permissions { err, .. } => Some(err),
+            Rename { err, .. }      => Some(err),
+            Remove { err, .. }      => Some(err),
+
+            #[cfg(feature = "lock")]
+            NoLockfile       => None,
+            #[cfg(feature = "lock")]
+            Lock { err, .. } => Some(err),
+
+            #[cfg(feature = "layout")]
+            SpecNotFound { .. } => None,
+            #[cfg(feature = "layout")]
+            Spec { err, .. }    => Some(err),
         }
     }
 }
@@ -58,6 +192,794 @@ impl From<std::convert::Infallible> for Error {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Describes what is wrong with the mode of a path, for [`Problem::WrongMode`].
+/// 
+/// # Arguments
+/// - `expected`: The expected mode.
+/// - `actual`: The actual mode.
+/// 
+/// # Returns
+/// A description like "world-readable, group-writable", or [`None`] if the path has no more permissions than expected (but lacks some).
+fn describe_mode(expected: u32, actual: u32) -> Option<String> {
+    let extra: u32 = actual & !expected;
+    let mut what: Vec<String> = vec![];
+    for (who, shift) in [ ("user", 6), ("group", 3), ("world", 0) ] {
+        for (bit, how) in [ (0o4, "readable"), (0o2, "writable"), (0o1, "executable") ] {
+            if extra & (bit << shift) != 0 { what.push(format!("{who}-{how}")); }
+        }
+    }
+    if !what.is_empty() { Some(what.join(", ")) } else { None }
+}
+
+/// Checks whether a path is a symlink as declared.
+/// 
+/// # Arguments
+/// - `fs`: The [`Fs`] on which the path lives.
+/// - `path`: The path to check, which is assumed to exist (without following symlinks).
+/// - `link`: The [`LinkTarget`] declared for it.
+/// 
+/// # Returns
+/// A [`Problem`] if the path is not a symlink as declared, or [`None`] if it is.
+fn check_link(fs: &dyn Fs, path: &Path, link: LinkTarget) -> Option<Problem> {
+    // Assert it's a link that points somewhere
+    if !fs.stat(path, false).map(|stat| stat.is_symlink).unwrap_or(false) { return Some(Problem::NotSymlink { path: path.into() }); }
+    let target: PathBuf = match fs.canonicalize(path) {
+        Ok(target) => target,
+        Err(_)     => { return Some(Problem::Dangling { path: path.into(), target: fs.read_link(path).unwrap_or_default() }); },
+    };
+
+    // Check if it stays inside the layout, if requested
+    if let LinkTarget::Inside(depth) = link {
+        let base: Option<PathBuf> = path.ancestors().nth(depth).and_then(|base| fs.canonicalize(base).ok());
+        if !base.map(|base| target.starts_with(base)).unwrap_or(false) { return Some(Problem::Escapes { path: path.into(), target }); }
+    }
+    None
+}
+
+/// Resolves a path to its canonical form, even if it does not exist (yet).
+/// 
+/// The longest existing ancestor is canonicalized (resolving any symlinks in it), after which the rest of the path is appended with any `.` and `..` resolved lexically.
+/// 
+/// # Arguments
+/// - `path`: The path to resolve.
+/// 
+/// # Returns
+/// The resolved path.
+/// 
+/// # Errors
+/// This function errors if we failed to canonicalize the existing part of the path.
+fn resolve(path: &Path) -> Result<PathBuf, Error> {
+    // Find the longest existing ancestor
+    let mut existing: &Path = path;
+    let mut rest: Vec<Component> = vec![];
+    while fs::symlink_metadata(existing).is_err() {
+        let mut components = existing.components();
+        match components.next_back() {
+            Some(last) => { rest.push(last); existing = components.as_path(); },
+            None       => break,
+        }
+    }
+
+    // Canonicalize it, then append the rest lexically
+    let mut res: PathBuf = if existing.as_os_str().is_empty() { std::env::current_dir() } else { fs::canonicalize(existing) }.map_err(|err| Error::Canonicalize { path: path.into(), err })?;
+    for component in rest.into_iter().rev() {
+        match component {
+            Component::CurDir    => {},
+            Component::ParentDir => { res.pop(); },
+            component            => res.push(component),
+        }
+    }
+    Ok(res)
+}
+
+/// Lists the entries of a directory to initialize dynamic fields with (e.g., [`HashMap<PathBuf, T>`]).
+/// 
+/// # Arguments
+/// - `base`: The directory to scan.
+/// - `opts`: The [`InitOptions`] that determine what to do with symlinks and which entries to collect.
+/// 
+/// # Returns
+/// The paths of the entries in the directory, or an empty list if it does not exist.
+/// 
+/// # Errors
+/// This function errors if we failed to read the directory, or if we found a symlink while the policy is [`SymlinkPolicy::Error`].
+pub(crate) fn scan(base: &Path, opts: &InitOptions) -> Result<Vec<PathBuf>, Error> {
+    #[cfg(feature = "cap-std")]
+    if let Some(cap) = &opts.cap { return crate::cap::scan(cap, base, opts); }
+
+    let entries: ReadDir = match fs::read_dir(base) {
+        Ok(entries) => entries,
+        Err(err) => {
+            // If we failed to read the directory because it does not exist, we conclude no files exist either
+            if err.kind() == ErrorKind::NotFound { return Ok(vec![]); }
+            // Otherwise, error hard
+            return Err(Error::DirRead { path: base.into(), err });
+        },
+    };
+    let mut paths: Vec<PathBuf> = vec![];
+    for (i, entry) in entries.enumerate() {
+        // Unwrap the entry
+        let entry: DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Error::DirEntryRead { path: base.into(), entry: i, err }); },
+        };
+
+        // Apply the glob patterns and regular expression
+        if !opts.name_matches(base, &entry.file_name()) { continue; }
+
+        // Apply the symlink policy
+        if opts.symlinks != SymlinkPolicy::Follow {
+            let is_link: bool = match entry.file_type() {
+                Ok(ty)   => ty.is_symlink(),
+                Err(err) => { return Err(Error::DirEntryRead { path: base.into(), entry: i, err }); },
+            };
+            if is_link {
+                match opts.symlinks {
+                    SymlinkPolicy::Skip   => { continue; },
+                    SymlinkPolicy::Error  => { return Err(Error::Symlink { path: entry.path() }); },
+                    SymlinkPolicy::Follow => unreachable!(),
+                }
+            }
+        }
+        let path: PathBuf = entry.path();
+        opts.check_confined(&path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Renders the given paths as a tree, one entry per line.
+/// 
+/// # Arguments
+/// - `entries`: The paths to render, relative to the root, together with their kind, whether they exist and the target if they are a symlink.
+/// 
+/// # Returns
+/// The rendered tree, where nested entries are indented by four spaces, directories end in a slash and missing entries are marked as such.
+fn render_tree(entries: &BTreeMap<PathBuf, (EntryKind, bool, Option<PathBuf>)>) -> String {
+    let mut res: String = String::new();
+    for (path, (kind, exists, target)) in entries {
+        let depth: usize = path.components().count();
+        let name: Cow<str> = if path.is_absolute() { path.to_string_lossy() } else { path.file_name().map(|n| n.to_string_lossy()).unwrap_or_else(|| path.to_string_lossy()) };
+        res.push_str(&"    ".repeat(if path.is_absolute() { 0 } else { depth - 1 }));
+        res.push_str(&name);
+        if *kind == EntryKind::Dir { res.push('/'); }
+        if let Some(target) = target { res.push_str(&format!(" -> {}", target.display())); }
+        if !exists { res.push_str(" [missing]"); }
+        res.push('\n');
+    }
+    res
+}
+
+/// Checks whether a path visited by [`DirectoryExt::walk()`] exists, for checking existence on a specific [`Fs`].
+/// 
+/// # Arguments
+/// - `fs`: The [`Fs`] on which the path lives.
+/// - `path`: The path to check.
+/// - `spec`: The [`FieldSpec`] with which it is declared.
+/// 
+/// # Errors
+/// This function errors if the path does not exist, in order to abort the walk.
+#[cfg(feature = "cap-std")]
+pub(crate) fn check_exists(fs: &dyn Fs, path: &Path, spec: &FieldSpec) -> Result<(), ()> {
+    // Note: symlinks are checked without following them
+    if fs.stat(path, spec.symlink.is_none()).is_some() { Ok(()) } else { Err(()) }
+}
+
+/// Implements [`DirectoryExt::validate()`] on the given [`Fs`].
+/// 
+/// # Arguments
+/// - `dir`: The [`DirectoryExt`] to validate.
+/// - `fs`: The [`Fs`] on which its paths live.
+/// 
+/// # Returns
+/// A list of [`Problem`]s, which is empty if everything is alright.
+fn validate_in<D: DirectoryExt>(dir: &D, fs: &dyn Fs) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = vec![];
+    let res: Result<(), std::convert::Infallible> = dir.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| {
+        let target: Option<Stat> = fs.stat(path, true);
+        let correct: bool = match spec.kind {
+            EntryKind::File => target.as_ref().map(|t| t.is_file).unwrap_or(false),
+            EntryKind::Dir  => target.as_ref().map(|t| t.is_dir).unwrap_or(false),
+            EntryKind::Any  => true,
+        };
+        // Note: symlinks are checked without following them first
+        let exists: bool = if spec.symlink.is_some() { fs.stat(path, false).is_some() } else { target.is_some() };
+        if !exists {
+            problems.push(Problem::Missing { path: path.into(), kind: spec.kind });
+        } else if let Some(problem) = spec.symlink.and_then(|link| check_link(fs, path, link)) {
+            problems.push(problem);
+        } else if !correct {
+            problems.push(Problem::WrongKind { path: path.into(), expected: spec.kind });
+        } else {
+            #[cfg(unix)]
+            if let (Some(expected), Some(target)) = (spec.mode, &target) {
+                let actual: u32 = target.mode & 0o7777;
+                if actual != expected { problems.push(Problem::WrongMode { path: path.into(), expected, actual }); }
+            }
+        }
+        Ok(())
+    });
+    match res {
+        Ok(())   => problems,
+        Err(err) => match err {},
+    }
+}
+
+/// Implements [`DirectoryExt::ensure_with()`] on the given [`Fs`].
+/// 
+/// # Arguments
+/// - `dir`: The [`DirectoryExt`] to create.
+/// - `fs`: The [`Fs`] on which its paths live.
+/// - `ctx`: The [`Context`] to render templates with.
+/// 
+/// # Errors
+/// This function errors if we failed to create any of the directories or files, or if we failed to render a template.
+fn ensure_in<D: DirectoryExt>(dir: &D, fs: &dyn Fs, ctx: &Context) -> Result<(), Error> {
+    dir.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| {
+        if fs.stat(path, true).is_some() || spec.symlink.is_some() { return Ok(()); }
+        match (spec.kind, spec.contents) {
+            (EntryKind::Dir, _) => {
+                if let Err(err) = fs.create_dir_all(path) { return Err(Error::DirCreate { path: path.into(), err }); }
+                #[cfg(unix)]
+                if let Some(mode) = spec.mode {
+                    if let Err(err) = fs.set_mode(path, mode) { return Err(Error::Permissions { path: path.into(), err }); }
+                }
+            },
+            (EntryKind::File, contents) | (EntryKind::Any, contents @ Some(_)) => {
+                // Generate the contents first, so we don't leave empty files behind if that fails
+                let bytes: Cow<[u8]> = match contents.map(|c| c.render(path, ctx)) {
+                    Some(Ok(bytes)) => bytes,
+                    Some(Err(err))  => { return Err(Error::Template { path: path.into(), err }); },
+                    None            => Cow::Borrowed(&[]),
+                };
+
+                if let Some(parent) = path.parent() {
+                    if let Err(err) = fs.create_dir_all(parent) { return Err(Error::DirCreate { path: parent.into(), err }); }
+                }
+
+                // Only create the file if it doesn't exist, to never touch existing files
+                let mut handle: Box<dyn Write> = match fs.create_new(path, spec.mode) {
+                    Ok(handle) => handle,
+                    Err(err) if err.kind() == ErrorKind::AlreadyExists => { return Ok(()); },
+                    Err(err) => { return Err(Error::FileWrite { path: path.into(), err }); },
+                };
+                if let Err(err) = handle.write_all(&bytes) { return Err(Error::FileWrite { path: path.into(), err }); }
+                #[cfg(unix)]
+                if let Some(mode) = spec.mode {
+                    if let Err(err) = fs.set_mode(path, mode) { return Err(Error::Permissions { path: path.into(), err }); }
+                }
+            },
+            (EntryKind::Any, None) => {},
+        }
+        Ok(())
+    })
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Abstracts over the filesystem on which paths are checked and created, i.e., either the ambient one or a capability (see [`DirectoryExt::validate_cap()`] and friends).
+pub(crate) trait Fs {
+    /// Returns what is at the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to examine.
+    /// - `follow`: Whether to follow the path if it's a symlink.
+    /// 
+    /// # Returns
+    /// A [`Stat`] describing it, or [`None`] if it does not exist (or we failed to examine it).
+    fn stat(&self, path: &Path, follow: bool) -> Option<Stat>;
+
+    /// Reads the target of the symlink at the given path.
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf>;
+
+    /// Resolves the given path to its canonical form.
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf>;
+
+    /// Creates the given directory and all of its parents that don't exist yet.
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()>;
+
+    /// Creates a new file at the given path for writing, failing with [`ErrorKind::AlreadyExists`] if it already exists.
+    /// 
+    /// On Unix, the file is created with the given mode (if any) to never expose its contents.
+    fn create_new(&self, path: &Path, mode: Option<u32>) -> std::io::Result<Box<dyn Write>>;
+
+    /// Sets the mode (e.g., `0o600`) of the given path.
+    #[cfg(unix)]
+    fn set_mode(&self, path: &Path, mode: u32) -> std::io::Result<()>;
+}
+
+/// Describes what is at a path, as returned by [`Fs::stat()`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Stat {
+    /// Whether it's a file.
+    pub(crate) is_file    : bool,
+    /// Whether it's a directory.
+    pub(crate) is_dir     : bool,
+    /// Whether it's a symlink (only if not followed).
+    pub(crate) is_symlink : bool,
+    /// The permissions of it. Always `0` on non-Unix platforms.
+    pub(crate) mode       : u32,
+}
+
+/// The ambient filesystem, i.e., [`std::fs`].
+pub(crate) struct Ambient;
+impl Fs for Ambient {
+    fn stat(&self, path: &Path, follow: bool) -> Option<Stat> {
+        let md: fs::Metadata = if follow { fs::metadata(path) } else { fs::symlink_metadata(path) }.ok()?;
+        #[cfg(unix)]
+        let mode: u32 = {
+            use std::os::unix::fs::PermissionsExt as _;
+            md.permissions().mode()
+        };
+        #[cfg(not(unix))]
+        let mode: u32 = 0;
+        Some(Stat { is_file: md.is_file(), is_dir: md.is_dir(), is_symlink: md.file_type().is_symlink(), mode })
+    }
+
+    #[inline]
+    fn read_link(&self, path: &Path) -> std::io::Result<PathBuf> { fs::read_link(path) }
+
+    #[inline]
+    fn canonicalize(&self, path: &Path) -> std::io::Result<PathBuf> { fs::canonicalize(path) }
+
+    #[inline]
+    fn create_dir_all(&self, path: &Path) -> std::io::Result<()> { fs::create_dir_all(path) }
+
+    fn create_new(&self, path: &Path, mode: Option<u32>) -> std::io::Result<Box<dyn Write>> {
+        let mut opts: OpenOptions = OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::OpenOptionsExt as _;
+            opts.mode(mode & 0o777);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        Ok(Box::new(opts.open(path)?))
+    }
+
+    #[cfg(unix)]
+    fn set_mode(&self, path: &Path, mode: u32) -> std::io::Result<()> {
+        use std::os::unix::fs::PermissionsExt as _;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+}
+
+
+
+/// Describes how a single path in a layout has been declared. This is passed to the visitor of [`DirectoryExt::walk()`].
+/// 
+/// For derived layouts, this is populated by the field's attributes (e.g., `#[file]` or `#[dir]`).
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub struct FieldSpec {
+    /// The kind of entry on disk that is expected at the path.
+    pub kind     : EntryKind,
+    /// The contents to write to the path by [`DirectoryExt::ensure()`] if it's a file that does not exist yet.
+    pub contents : Option<Contents>,
+    /// The permissions (e.g., `0o600`) that the path must have, as given by `#[file(mode = ...)]` or `#[dir(mode = ...)]`.
+    /// 
+    /// This is only enforced and checked on Unix, and ignored elsewhere.
+    pub mode     : Option<u32>,
+    /// Whether the path must be a symlink, as given by `#[file(symlink)]` or `#[dir(symlink)]`. If so, `kind` and `mode` refer to its target.
+    pub symlink  : Option<LinkTarget>,
+}
+impl FieldSpec {
+    /// Constructor for the FieldSpec that declares a path of the given kind, with everything else default.
+    /// 
+    /// # Arguments
+    /// - `kind`: The [`EntryKind`] that is expected at the path.
+    /// 
+    /// # Returns
+    /// A new FieldSpec instance.
+    #[inline]
+    pub fn new(kind: EntryKind) -> Self { Self { kind, contents: None, mode: None, symlink: None } }
+}
+
+/// Defines where a symlink may point to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LinkTarget {
+    /// It may point anywhere (`#[file(symlink)]`).
+    Anywhere,
+    /// It must point inside the directory of the layout that declares it (`#[file(symlink = "inside")]`). That directory is the given number of levels above the symlink.
+    Inside(usize),
+}
+
+/// Defines what to do with symlinks found while scanning dynamic fields (e.g., [`HashMap<PathBuf, T>`] or [`Dynamic<T>`](crate::std::Dynamic)).
+/// 
+/// For derived layouts, this is set by `#[directories(symlinks = "follow" | "skip" | "error")]`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SymlinkPolicy {
+    /// Symlinks are treated like whatever they point to.
+    #[default]
+    Follow,
+    /// Symlinks are ignored.
+    Skip,
+    /// Symlinks cause initialization to fail with [`Error::Symlink`].
+    Error,
+}
+
+/// Defines what to do with entries of which the name is not valid UTF-8 while scanning dynamic fields keyed by [`String`] (e.g., [`Named<T>`](crate::std::Named) or [`Keyed<K, T>`](crate::std::Keyed)).
+/// 
+/// For derived layouts, this is set by `#[directories(names = "skip" | "error")]`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NamePolicy {
+    /// Such entries cause initialization to fail with [`Error::NonUtf8Name`].
+    #[default]
+    Error,
+    /// Such entries are ignored.
+    Skip,
+}
+
+/// Defines what to do with entries of which the name cannot be parsed as a key while scanning dynamic fields keyed by it (e.g., [`Keyed<K, T>`](crate::std::Keyed)).
+/// 
+/// This also applies to entries of which the name parses to a key that another entry already has (e.g., `01` and `1` as integers). Of such entries, the one with the smallest path is kept, and the others are treated as if their name could not be parsed (failing with [`Error::DuplicateKey`] instead of [`Error::Key`]).
+/// 
+/// By default, such entries are skipped, so that a stray file (e.g., a `README.md` among versioned release directories) doesn't fail the whole layout. With the `log`-feature enabled, a warning is logged for every skipped entry.
+/// 
+/// For derived layouts, this is set by `#[directories(keys = "skip" | "error" | "collect")]`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum KeyPolicy {
+    /// Such entries cause initialization to fail with [`Error::Key`] (or [`Error::DuplicateKey`]).
+    Error,
+    /// Such entries are ignored (logging a warning if the `log`-feature is enabled).
+    #[default]
+    Skip,
+    /// Such entries are collected separately (see [`Keyed::rejects()`](crate::std::Keyed::rejects())).
+    Collect,
+}
+
+/// Defines options that change how a [`Directory`] is initialized. Given to [`Directory::try_init_with()`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct InitOptions {
+    /// What to do with symlinks found while scanning dynamic fields.
+    pub symlinks : SymlinkPolicy,
+    /// What to do with entries of which the name is not valid UTF-8 while scanning dynamic fields keyed by [`String`].
+    pub names    : NamePolicy,
+    /// What to do with entries of which the name cannot be parsed as a key while scanning dynamic fields keyed by it.
+    pub keys     : KeyPolicy,
+    /// If given, all paths must resolve to somewhere inside this (canonical) root. Set by [`Directory::try_init_confined()`].
+    pub confine  : Option<PathBuf>,
+    /// If given, all paths are relative to this capability instead of the ambient filesystem. Set by [`Directory::try_init_cap()`].
+    #[cfg(feature = "cap-std")]
+    pub cap      : Option<Arc<cap_std::fs::Dir>>,
+    /// If given, dynamic fields scanning the given directory only collect entries of which the name matches the given patterns. Set by `#[dir(glob = ...)]` or [`InitOptions::with_glob()`].
+    pub glob     : Option<(PathBuf, Patterns)>,
+    /// If given, dynamic fields scanning the given directory only collect entries of which the name matches the given regular expression. Set by `#[dir(regex = ...)]` or [`InitOptions::with_regex()`].
+    #[cfg(feature = "regex")]
+    pub regex    : Option<(PathBuf, Regex)>,
+}
+impl InitOptions {
+    /// Returns a copy of these options that only collects entries matching the given patterns when scanning the given directory.
+    /// 
+    /// Because the patterns are tied to the directory, they don't affect any dynamic fields nested in the entries found.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory of the dynamic field to filter.
+    /// - `patterns`: The [`Patterns`] that the names of the entries must match.
+    /// 
+    /// # Returns
+    /// A new InitOptions with [`InitOptions::glob`] set.
+    #[inline]
+    pub fn with_glob(&self, dir: impl Into<PathBuf>, patterns: Patterns) -> Self {
+        let mut opts: Self = self.clone();
+        opts.glob = Some((dir.into(), patterns));
+        opts
+    }
+
+    /// Returns a copy of these options that only collects entries matching the given regular expression when scanning the given directory.
+    /// 
+    /// Like with [`InitOptions::with_glob()`], the regular expression doesn't affect any dynamic fields nested in the entries found.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory of the dynamic field to filter.
+    /// - `pattern`: The regular expression that the names of the entries must match.
+    /// 
+    /// # Returns
+    /// A new InitOptions with [`InitOptions::regex`] set.
+    /// 
+    /// # Errors
+    /// This function errors if the regular expression is invalid.
+    #[cfg(feature = "regex")]
+    pub fn with_regex(&self, dir: impl Into<PathBuf>, pattern: &str) -> Result<Self, Error> {
+        let regex: Regex = Regex::new(pattern).map_err(|err| Error::Regex { pattern: pattern.into(), err })?;
+        let mut opts: Self = self.clone();
+        opts.regex = Some((dir.into(), regex));
+        Ok(opts)
+    }
+
+    /// Returns a copy of these options that only collects entries matching the given regular expression when scanning the given directory, compiling it only once.
+    /// 
+    /// This is like [`InitOptions::with_regex()`], except that the compiled regular expression is kept in the given cache and reused on subsequent calls. This is what `#[dir(regex = ...)]` uses, such that initializing a layout multiple times doesn't compile it every time.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory of the dynamic field to filter.
+    /// - `pattern`: The regular expression that the names of the entries must match.
+    /// - `cache`: A [`OnceLock`] that holds the compiled `pattern` once it has been compiled. It should only ever be used with the same `pattern`.
+    /// 
+    /// # Returns
+    /// A new InitOptions with [`InitOptions::regex`] set.
+    /// 
+    /// # Errors
+    /// This function errors if the regular expression is invalid.
+    #[cfg(feature = "regex")]
+    pub fn with_regex_cached(&self, dir: impl Into<PathBuf>, pattern: &str, cache: &OnceLock<Regex>) -> Result<Self, Error> {
+        let regex: &Regex = match cache.get() {
+            Some(regex) => regex,
+            None => {
+                let regex: Regex = Regex::new(pattern).map_err(|err| Error::Regex { pattern: pattern.into(), err })?;
+                cache.get_or_init(|| regex)
+            },
+        };
+        let mut opts: Self = self.clone();
+        opts.regex = Some((dir.into(), regex.clone()));
+        Ok(opts)
+    }
+
+    /// Checks whether an entry found while scanning the given directory should be collected according to [`InitOptions::glob`] and [`InitOptions::regex`].
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory that is being scanned.
+    /// - `name`: The file name of the entry.
+    /// 
+    /// # Returns
+    /// True if there are no patterns for this directory or the name matches them, or false otherwise.
+    pub(crate) fn name_matches(&self, dir: &Path, name: &OsStr) -> bool {
+        if let Some((glob_dir, patterns)) = &self.glob {
+            if glob_dir == dir && !patterns.matches(name) { return false; }
+        }
+        #[cfg(feature = "regex")]
+        if let Some((regex_dir, regex)) = &self.regex {
+            if regex_dir == dir && !regex.is_match(&name.to_string_lossy()) { return false; }
+        }
+        true
+    }
+
+    /// Checks whether the given path exists, relative to the capability in [`InitOptions::cap`] if there is any.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to check.
+    /// 
+    /// # Returns
+    /// True if it exists, or false otherwise.
+    pub fn exists(&self, path: &Path) -> bool {
+        #[cfg(feature = "cap-std")]
+        if let Some(cap) = &self.cap { return cap.exists(crate::cap::rel(path)); }
+        path.exists()
+    }
+
+    /// Checks whether the given (initialized) nested directory exists, relative to the capability in [`InitOptions::cap`] if there is any.
+    /// 
+    /// This is what dynamic types use to filter the entries they found, since [`DirectoryExt::exists()`] always checks the ambient filesystem.
+    /// 
+    /// # Arguments
+    /// - `nested`: The nested directory to check.
+    /// 
+    /// # Returns
+    /// True if it exists, or false otherwise.
+    pub fn nested_exists<T: DirectoryExt>(&self, nested: &T) -> bool {
+        #[cfg(feature = "cap-std")]
+        if let Some(cap) = &self.cap { return nested.exists_cap(cap); }
+        nested.exists()
+    }
+
+    /// Resolves the given path to its canonical form if it is a directory, relative to the capability in [`InitOptions::cap`] if there is any.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to resolve.
+    /// 
+    /// # Returns
+    /// The canonical path, or [`None`] if the path is not a directory (or does not exist).
+    /// 
+    /// # Errors
+    /// This function errors if we failed to canonicalize the directory.
+    pub(crate) fn canonical_dir(&self, path: &Path) -> Result<Option<PathBuf>, Error> {
+        #[cfg(feature = "cap-std")]
+        if let Some(cap) = &self.cap {
+            let rel: &Path = crate::cap::rel(path);
+            if !cap.is_dir(rel) { return Ok(None); }
+            return cap.canonicalize(rel).map(Some).map_err(|err| Error::Canonicalize { path: path.into(), err });
+        }
+        if !path.is_dir() { return Ok(None); }
+        fs::canonicalize(path).map(Some).map_err(|err| Error::Canonicalize { path: path.into(), err })
+    }
+
+    /// Checks whether the given path stays within the root given by [`InitOptions::confine`], if any.
+    /// 
+    /// The path is resolved to its canonical form first, so symlinks and `..` that point outside of the root are caught too.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to check.
+    /// 
+    /// # Errors
+    /// This function errors with [`Error::Escape`] if the path resolves to outside of the root, or if we failed to resolve it.
+    pub fn check_confined(&self, path: &Path) -> Result<(), Error> {
+        let root: &Path = match &self.confine {
+            Some(root) => root,
+            None       => { return Ok(()); },
+        };
+        let resolved: PathBuf = resolve(path)?;
+        if !resolved.starts_with(root) { return Err(Error::Escape { path: path.into(), resolved, root: root.into() }); }
+        Ok(())
+    }
+}
+
+/// Defines a lock on a layout, as returned by [`DirectoryExt::lock()`] and friends.
+/// 
+/// The lock is an advisory `flock`-style lock on the layout's lock file (see `#[directories(lockfile = ...)]`), and is released when this LayoutLock is dropped.
+/// 
+/// It is implemented with [`File::lock()`] and friends, which is why it requires the `lock` feature, and that feature requires Rust 1.89 or newer.
+#[cfg(feature = "lock")]
+#[derive(Debug)]
+pub struct LayoutLock {
+    /// The path of the lock file.
+    path   : PathBuf,
+    /// The handle to the lock file that holds the lock.
+    handle : File,
+}
+#[cfg(feature = "lock")]
+impl LayoutLock {
+    /// Acquires a lock on the given lock file, creating it if it does not exist.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the lock file, or [`None`] if the layout doesn't declare one.
+    /// - `shared`: Whether to acquire a shared lock instead of an exclusive one.
+    /// - `block`: Whether to wait for the lock to become available.
+    /// 
+    /// # Returns
+    /// A new LayoutLock, or [`None`] if `block` is false and the lock is held elsewhere.
+    /// 
+    /// # Errors
+    /// This function errors if `path` is [`None`], or if we failed to create, open or lock the lock file.
+    fn acquire(path: Option<PathBuf>, shared: bool, block: bool) -> Result<Option<Self>, Error> {
+        let path: PathBuf = path.ok_or(Error::NoLockfile)?;
+        if let Some(parent) = path.parent() {
+            if let Err(err) = fs::create_dir_all(parent) { return Err(Error::DirCreate { path: parent.into(), err }); }
+        }
+        let handle: File = match OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::Lock { path, err }); },
+        };
+
+        // Lock it
+        let res: Result<(), TryLockError> = match (shared, block) {
+            (false, true)  => handle.lock().map_err(TryLockError::Error),
+            (true, true)   => handle.lock_shared().map_err(TryLockError::Error),
+            (false, false) => handle.try_lock(),
+            (true, false)  => handle.try_lock_shared(),
+        };
+        match res {
+            Ok(())                        => Ok(Some(Self { path, handle })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => Err(Error::Lock { path, err }),
+        }
+    }
+
+    /// Returns the path of the lock file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+}
+#[cfg(feature = "lock")]
+impl Drop for LayoutLock {
+    #[inline]
+    fn drop(&mut self) {
+        // Closing the handle also releases the lock, but let's be explicit
+        let _ = self.handle.unlock();
+    }
+}
+
+/// Defines the default contents of a file, as given by `#[file(default = ...)]`, `#[file(default_from = ...)]`, `#[file(default_fn = ...)]` or `#[file(template = ...)]`.
+#[derive(Clone, Copy, Debug)]
+pub enum Contents {
+    /// The contents are known at compile time.
+    Bytes(&'static [u8]),
+    /// The contents are generated by calling a function.
+    Fn(fn() -> Vec<u8>),
+    /// The contents are rendered from a template (see [`crate::template`]).
+    Template(&'static str),
+}
+impl Contents {
+    /// Returns the contents as bytes, generating or rendering them if necessary.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file for which the contents are generated, which is available to templates.
+    /// - `ctx`: The [`Context`] to render templates with.
+    /// 
+    /// # Returns
+    /// The bytes to write to the file.
+    /// 
+    /// # Errors
+    /// This function errors if the contents are a template that failed to render.
+    pub fn render(&self, path: &Path, ctx: &Context) -> Result<Cow<'static, [u8]>, crate::template::Error> {
+        match self {
+            Self::Bytes(bytes)       => Ok(Cow::Borrowed(bytes)),
+            Self::Fn(f)              => Ok(Cow::Owned(f())),
+            Self::Template(template) => ctx.render(template, path).map(|s| Cow::Owned(s.into_bytes())),
+        }
+    }
+}
+
+
+
+/// Defines a problem found by [`DirectoryExt::validate()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Problem {
+    /// A mandatory path does not exist.
+    Missing { path: PathBuf, kind: EntryKind },
+    /// A path exists, but is not of the declared kind.
+    WrongKind { path: PathBuf, expected: EntryKind },
+    /// A path exists, but does not have the declared permissions.
+    WrongMode { path: PathBuf, expected: u32, actual: u32 },
+    /// A path exists, but is not a symlink while it is declared to be.
+    NotSymlink { path: PathBuf },
+    /// A symlink points to something that does not exist.
+    Dangling { path: PathBuf, target: PathBuf },
+    /// A symlink points outside of the layout while it is declared to stay inside.
+    Escapes { path: PathBuf, target: PathBuf },
+}
+impl Problem {
+    /// Returns the path to which this problem relates.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        use Problem::*;
+        match self {
+            Missing { path, .. }   => path,
+            WrongKind { path, .. } => path,
+            WrongMode { path, .. } => path,
+            NotSymlink { path }    => path,
+            Dangling { path, .. }  => path,
+            Escapes { path, .. }   => path,
+        }
+    }
+
+    /// Returns the path to which this problem relates, mutably.
+    #[cfg(feature = "cap-std")]
+    #[inline]
+    pub(crate) fn path_mut(&mut self) -> &mut PathBuf {
+        use Problem::*;
+        match self {
+            Missing { path, .. }   => path,
+            WrongKind { path, .. } => path,
+            WrongMode { path, .. } => path,
+            NotSymlink { path }    => path,
+            Dangling { path, .. }  => path,
+            Escapes { path, .. }   => path,
+        }
+    }
+}
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Problem::*;
+        match self {
+            Missing { path, kind: EntryKind::File } => write!(f, "Missing file '{}'", path.display()),
+            Missing { path, kind: EntryKind::Dir }  => write!(f, "Missing directory '{}'", path.display()),
+            Missing { path, kind: EntryKind::Any }  => write!(f, "Missing '{}'", path.display()),
+
+            WrongKind { path, expected: EntryKind::File } => write!(f, "'{}' is not a file", path.display()),
+            WrongKind { path, expected: EntryKind::Dir }  => write!(f, "'{}' is not a directory", path.display()),
+            WrongKind { path, expected: EntryKind::Any }  => write!(f, "'{}' is of the wrong kind", path.display()),
+
+            WrongMode { path, expected, actual } => match describe_mode(*expected, *actual) {
+                Some(what) => write!(f, "'{}' is {} (mode {:#o}, expected {:#o})", path.display(), what, actual, expected),
+                None       => write!(f, "'{}' has mode {:#o}, expected {:#o}", path.display(), actual, expected),
+            },
+
+            NotSymlink { path }       => write!(f, "'{}' is not a symlink", path.display()),
+            Dangling { path, target } => write!(f, "Symlink '{}' points to '{}', which does not exist", path.display(), target.display()),
+            Escapes { path, target }  => write!(f, "Symlink '{}' points to '{}', which is outside of the layout", path.display(), target.display()),
+        }
+    }
+}
+
+
+
+
+
 /***** DEFAULT IMPLEMENTATIONS *****/
 // Default implementation for the [`PathBuf`].
 impl Directory for PathBuf {
@@ -65,20 +987,30 @@ impl Directory for PathBuf {
 
     #[inline]
     fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Ok(PathBuf::from(base.into())) }
+
+    // Paths don't access the filesystem, so they're happy with any options
+    #[inline]
+    fn try_init_with(base: impl Into<PathBuf>, _opts: &InitOptions) -> Result<Self, Self::Error> { Self::try_init(base) }
 }
 impl DirectoryExt for PathBuf {
     #[inline]
     fn exists(&self) -> bool { <Path>::exists(self) }
+
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> { visit(self, spec) }
 }
 
 // Default implementation for the [`Option<impl Directory>`] type, which can be used to only instantiate it if it exists.
 impl<T: Directory> Directory for Option<T> {
-    type Error = std::convert::Infallible;
+    type Error = T::Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
 
-    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> {
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
         let base: PathBuf = base.into();
-        if base.exists() {
-            Ok(Some(T::init(base)))
+        if opts.exists(&base) {
+            Ok(Some(T::try_init_with(base, opts)?))
         } else {
             Ok(None)
         }
@@ -92,36 +1024,34 @@ impl<T: DirectoryExt> DirectoryExt for Option<T> {
             None       => true,
         }
     }
+
+    #[inline]
+    fn lockfile(&self) -> Option<PathBuf> { self.as_ref().and_then(T::lockfile) }
+
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        match self {
+            Some(nested) => nested.walk(spec, visit),
+            None         => Ok(()),
+        }
+    }
 }
 
 // Default implementation for the [`HashMap<PathBuf, impl Directory>`] type, which can be used to dynamically scan for directories.
 impl<T: Directory> Directory for HashMap<PathBuf, T> where Error: From<T::Error> {
     type Error = Error;
 
-    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> {
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
         let base: PathBuf = base.into();
 
         // Scan the directory for directories
         let mut result: HashMap<PathBuf, T> = HashMap::new();
-        let entries: ReadDir = match fs::read_dir(&base) {
-            Ok(entries) => entries,
-            Err(err) => {
-                // If we failed to read the directory because it does not exist, we conclude no files exist either
-                if err.kind() == ErrorKind::NotFound { return Ok(HashMap::new()); }
-                // Otherwise, error hard
-                return Err(Error::DirRead { path: base, err });
-            },
-        };
-        for (i, entry) in entries.enumerate() {
-            // Unwrap the entry
-            let entry: DirEntry = match entry {
-                Ok(entry) => entry,
-                Err(err)  => { return Err(Error::DirEntryRead { path: base, entry: i, err }); },
-            };
-
+        for entry_path in scan(&base, opts)? {
             // Initialize the entry
-            let entry_path: PathBuf = entry.path();
-            let nested: T = T::try_init(&entry_path)?;
+            let nested: T = T::try_init_with(&entry_path, opts)?;
 
             // Add the entry to the dynamic set
             result.insert(entry_path, nested);
@@ -140,6 +1070,188 @@ impl<T: DirectoryExt> DirectoryExt for HashMap<PathBuf, T> where Error: From<T::
         }
         exists
     }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Walk in order of path, to be deterministic
+        let mut entries: Vec<(&PathBuf, &T)> = self.iter().collect();
+        entries.sort_by_key(|(path, _)| *path);
+        for (_, nested) in entries {
+            nested.walk(spec, visit)?;
+        }
+        Ok(())
+    }
+}
+
+// Default implementation for the [`BTreeMap<PathBuf, impl Directory>`] type, which can be used to dynamically scan for directories in order of path.
+impl<T: Directory> Directory for BTreeMap<PathBuf, T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories
+        let mut result: BTreeMap<PathBuf, T> = BTreeMap::new();
+        for entry_path in scan(&base, opts)? {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Add the entry to the dynamic set
+            result.insert(entry_path, nested);
+        }
+
+        // Done, return the found entries
+        Ok(result)
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for BTreeMap<PathBuf, T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        for nested in self.values() {
+            nested.walk(spec, visit)?;
+        }
+        Ok(())
+    }
+}
+
+// Default implementation for the [`IndexMap<PathBuf, impl Directory>`] type, which can be used to dynamically scan for directories in order of path while keeping any entries inserted later in insertion order.
+#[cfg(feature = "indexmap")]
+impl<T: Directory> Directory for IndexMap<PathBuf, T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories, sorted to have a predictable insertion order
+        let mut paths: Vec<PathBuf> = scan(&base, opts)?;
+        paths.sort();
+        let mut result: IndexMap<PathBuf, T> = IndexMap::with_capacity(paths.len());
+        for entry_path in paths {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Add the entry to the dynamic set
+            result.insert(entry_path, nested);
+        }
+
+        // Done, return the found entries
+        Ok(result)
+    }
+}
+#[cfg(feature = "indexmap")]
+impl<T: DirectoryExt> DirectoryExt for IndexMap<PathBuf, T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        for nested in self.values() {
+            nested.walk(spec, visit)?;
+        }
+        Ok(())
+    }
+}
+
+// Default implementation for the [`Vec<impl Directory>`] type, which can be used to dynamically scan for directories in order of path.
+impl<T: Directory> Directory for Vec<T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories, sorted by path
+        let mut paths: Vec<PathBuf> = scan(&base, opts)?;
+        paths.sort();
+        let mut result: Vec<T> = Vec::with_capacity(paths.len());
+        for entry_path in paths {
+            result.push(T::try_init_with(entry_path, opts)?);
+        }
+
+        // Done, return the found entries
+        Ok(result)
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for Vec<T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        for nested in self {
+            nested.walk(spec, visit)?;
+        }
+        Ok(())
+    }
+}
+
+// Default implementation for the [`Vec<(PathBuf, impl Directory)>`] type, which can be used to dynamically scan for directories in order of path.
+impl<T: Directory> Directory for Vec<(PathBuf, T)> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories, sorted by path
+        let mut paths: Vec<PathBuf> = scan(&base, opts)?;
+        paths.sort();
+        let mut result: Vec<(PathBuf, T)> = Vec::with_capacity(paths.len());
+        for entry_path in paths {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Add the entry to the dynamic set
+            result.push((entry_path, nested));
+        }
+
+        // Done, return the found entries
+        Ok(result)
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for Vec<(PathBuf, T)> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for (_, nested) in self {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        for (_, nested) in self {
+            nested.walk(spec, visit)?;
+        }
+        Ok(())
+    }
 }
 
 
@@ -176,6 +1288,74 @@ pub trait Directory: Sized {
     /// # Errors
     /// This function may errors if this field failed to initialize or any of the fields errors when initializing them. This may be in the case of dynamic fields, such as a [`HashMap<PathBuf, T>`].
     fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error>;
+
+    /// Initializes the directory by deducing all of the paths, with the given options.
+    /// 
+    /// The default implementation ignores the options and calls [`Directory::try_init()`]. Types that contain other Directory types or access the filesystem should override it to pass the options on.
+    /// 
+    /// # Arguments
+    /// - `base`: A [`Path`] that defines the base for any relative paths in this directory.
+    /// - `opts`: The [`InitOptions`] that change how the directory is initialized (e.g., what to do with symlinks when scanning).
+    /// 
+    /// # Returns
+    /// A new instance of Self with all the [`PathBuf`] fields (and other Directory fields) properly initialized.
+    /// 
+    /// # Errors
+    /// This function may errors if this field failed to initialize or any of the fields errors when initializing them.
+    /// 
+    /// # Panics
+    /// The default implementation panics if the options contain a capability (see [`Directory::try_init_cap()`]), since it would silently resolve paths against the current directory instead.
+    #[inline]
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        #[cfg(feature = "cap-std")]
+        if opts.cap.is_some() { panic!("{} does not support being initialized relative to a capability (override `Directory::try_init_with()` to support it)", std::any::type_name::<Self>()); }
+        let _ = opts;
+        Self::try_init(base)
+    }
+
+    /// Initializes the directory by deducing all of the paths, asserting that none of them escape the base.
+    /// 
+    /// Every resolved path (including dynamically scanned entries) is canonicalized, so any absolute paths, `..` or symlinks that lead outside of the base are caught. Use this when initializing layouts in untrusted directories.
+    /// 
+    /// Paths are already checked while initializing if [`Directory::try_init_with()`] passes the options on (which the derived implementations and those in this crate do), so that nothing outside of the base is scanned. Afterwards, every path that the result [walks](DirectoryExt::walk()) is checked again, which catches implementations that ignore the options too. Note that this includes symlinks declared with `#[file(symlink)]`, which thus cannot point outside of the base either.
+    /// 
+    /// # Arguments
+    /// - `base`: A [`Path`] that defines the base for any relative paths in this directory.
+    /// 
+    /// # Returns
+    /// A new instance of Self with all the [`PathBuf`] fields (and other Directory fields) properly initialized.
+    /// 
+    /// # Errors
+    /// This function errors with [`Error::Escape`] if any path resolves to outside of the base, or if initialization fails otherwise.
+    fn try_init_confined(base: impl Into<PathBuf>) -> Result<Self, Self::Error> where Self: DirectoryExt, Self::Error: From<Error> {
+        let base: PathBuf = base.into();
+        let opts: InitOptions = InitOptions { confine: Some(resolve(&base)?), ..Default::default() };
+        let this: Self = Self::try_init_with(base, &opts)?;
+
+        // Check every path we ended up with, in case the implementation didn't
+        this.walk(&FieldSpec::default(), &mut |path: &Path, _: &FieldSpec| opts.check_confined(path))?;
+        Ok(this)
+    }
+
+    /// Initializes the directory relative to the given capability instead of a path on the ambient filesystem.
+    /// 
+    /// All paths in the initialized directory are relative to the capability, and cannot escape it. Use the types in [`crate::cap`] for fields that should hold handles opened through it.
+    /// 
+    /// Note that this relies on [`Directory::try_init_with()`] passing the options on, which the derived implementations and those in this crate do.
+    /// 
+    /// # Arguments
+    /// - `dir`: The [`cap_std::fs::Dir`] that acts as the root of this directory.
+    /// 
+    /// # Returns
+    /// A new instance of Self with all the fields properly initialized.
+    /// 
+    /// # Errors
+    /// This function may errors if this field failed to initialize or any of the fields errors when initializing them.
+    #[cfg(feature = "cap-std")]
+    fn try_init_cap(dir: cap_std::fs::Dir) -> Result<Self, Self::Error> {
+        let opts: InitOptions = InitOptions { cap: Some(Arc::new(dir)), ..Default::default() };
+        Self::try_init_with(PathBuf::new(), &opts)
+    }
 }
 
 
@@ -186,7 +1366,375 @@ pub trait DirectoryExt: Directory {
     /// 
     /// In the case of optional paths, we do check if mandatory sub-paths exist if the path itself exists.
     /// 
+    /// This checks the ambient filesystem. For directories initialized with [`Directory::try_init_cap()`], use [`DirectoryExt::exists_cap()`] instead.
+    /// 
     /// # Returns
     /// True if they do, false if they don't.
     fn exists(&self) -> bool;
+
+    /// Returns the path of the lock file of this directory, as declared by `#[directories(lockfile = ...)]`.
+    /// 
+    /// The default implementation returns [`None`], i.e., no lock file.
+    /// 
+    /// # Returns
+    /// The path of the lock file, or [`None`] if this directory does not declare any.
+    #[inline]
+    fn lockfile(&self) -> Option<PathBuf> { None }
+
+    /// Acquires an exclusive lock on this directory, waiting until it becomes available.
+    /// 
+    /// The lock is advisory, i.e., it only excludes other processes that lock the same directory. It is released when the returned [`LayoutLock`] is dropped.
+    /// 
+    /// # Returns
+    /// A [`LayoutLock`] that holds the lock.
+    /// 
+    /// # Errors
+    /// This function errors if this directory does not declare a lock file (see [`DirectoryExt::lockfile()`]), or if we failed to create or lock it.
+    #[cfg(feature = "lock")]
+    #[inline]
+    fn lock(&self) -> Result<LayoutLock, Error> { LayoutLock::acquire(self.lockfile(), false, true).map(|lock| lock.expect("Blocking lock did not wait for the lock")) }
+
+    /// Acquires a shared lock on this directory, waiting until it becomes available.
+    /// 
+    /// Any number of shared locks can be held at the same time, but not while an exclusive lock is held (see [`DirectoryExt::lock()`]).
+    /// 
+    /// # Returns
+    /// A [`LayoutLock`] that holds the lock.
+    /// 
+    /// # Errors
+    /// This function errors if this directory does not declare a lock file (see [`DirectoryExt::lockfile()`]), or if we failed to create or lock it.
+    #[cfg(feature = "lock")]
+    #[inline]
+    fn lock_shared(&self) -> Result<LayoutLock, Error> { LayoutLock::acquire(self.lockfile(), true, true).map(|lock| lock.expect("Blocking lock did not wait for the lock")) }
+
+    /// Attempts to acquire an exclusive lock on this directory without waiting.
+    /// 
+    /// # Returns
+    /// A [`LayoutLock`] that holds the lock, or [`None`] if it is currently held elsewhere.
+    /// 
+    /// # Errors
+    /// This function errors if this directory does not declare a lock file (see [`DirectoryExt::lockfile()`]), or if we failed to create or lock it.
+    #[cfg(feature = "lock")]
+    #[inline]
+    fn try_lock(&self) -> Result<Option<LayoutLock>, Error> { LayoutLock::acquire(self.lockfile(), false, false) }
+
+    /// Attempts to acquire a shared lock on this directory without waiting.
+    /// 
+    /// # Returns
+    /// A [`LayoutLock`] that holds the lock, or [`None`] if an exclusive lock is currently held elsewhere.
+    /// 
+    /// # Errors
+    /// This function errors if this directory does not declare a lock file (see [`DirectoryExt::lockfile()`]), or if we failed to create or lock it.
+    #[cfg(feature = "lock")]
+    #[inline]
+    fn try_lock_shared(&self) -> Result<Option<LayoutLock>, Error> { LayoutLock::acquire(self.lockfile(), true, false) }
+
+    /// Visits every path in this directory, together with how it was declared.
+    /// 
+    /// Only paths that have been initialized are visited, i.e., missing optional paths and dynamic entries that were not found are skipped. Dynamic entries are visited in order of their path, so the order is deterministic. The dynamic types in [`crate::std`] visit their own directory with `spec` before their entries, which are declared by their own type; plain maps like [`HashMap<PathBuf, T>`] don't know their own path, and pass `spec` on to their entries instead.
+    /// 
+    /// The default implementation visits nothing, so any manual implementation that contains paths should override it.
+    /// 
+    /// # Arguments
+    /// - `spec`: The [`FieldSpec`] with which this directory itself has been declared in its parent (or [`FieldSpec::default()`] if it's the root).
+    /// - `visit`: The closure to call for every path. If it errors, the walk is aborted.
+    /// 
+    /// # Errors
+    /// This function errors if `visit` errors.
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        let _ = (spec, visit);
+        Ok(())
+    }
+
+    /// Validates this directory, returning a [`Problem`] for every path that is not as declared.
+    /// 
+    /// This is like [`DirectoryExt::exists()`], except that it tells you what is wrong and that it also checks that paths declared as `#[file]` or `#[dir]` are indeed files or directories, that symlinks are as declared, and (on Unix) that paths have the declared `mode`.
+    /// 
+    /// This checks the ambient filesystem. For directories initialized with [`Directory::try_init_cap()`], use [`DirectoryExt::validate_cap()`] instead.
+    /// 
+    /// # Returns
+    /// A list of [`Problem`]s, which is empty if everything is alright.
+    #[inline]
+    fn validate(&self) -> Vec<Problem> { validate_in(self, &Ambient) }
+
+    /// Validates this directory on the given capability, returning a [`Problem`] for every path that is not as declared.
+    /// 
+    /// This is like [`DirectoryExt::validate()`], except that paths are interpreted relative to the capability instead of on the ambient filesystem. Use this for directories initialized with [`Directory::try_init_cap()`].
+    /// 
+    /// # Arguments
+    /// - `cap`: The [`Dir`](cap_std::fs::Dir) capability that the paths in this directory are relative to.
+    /// 
+    /// # Returns
+    /// A list of [`Problem`]s, which is empty if everything is alright.
+    #[cfg(feature = "cap-std")]
+    #[inline]
+    fn validate_cap(&self, cap: &cap_std::fs::Dir) -> Vec<Problem> { validate_in(self, cap) }
+
+    /// Returns if all mandatory paths in this directory exist on the given capability.
+    /// 
+    /// This is like [`DirectoryExt::exists()`], except that paths are interpreted relative to the capability instead of on the ambient filesystem. Use this for directories initialized with [`Directory::try_init_cap()`].
+    /// 
+    /// # Arguments
+    /// - `cap`: The [`Dir`](cap_std::fs::Dir) capability that the paths in this directory are relative to.
+    /// 
+    /// # Returns
+    /// True if they do, false if they don't.
+    #[cfg(feature = "cap-std")]
+    #[inline]
+    fn exists_cap(&self, cap: &cap_std::fs::Dir) -> bool { self.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| check_exists(cap, path, spec)).is_ok() }
+
+    /// Renders this directory as a deterministic, human-readable tree, e.g., for printing or snapshot testing.
+    /// 
+    /// Every path is rendered relative to the given root, sorted by path and indented by its depth. Intermediate directories that are not declared themselves are included too. Directories end in a slash, symlinks show their target and missing paths are marked with `[missing]`. Paths that are not in the root are rendered in full.
+    /// 
+    /// For example:
+    /// ```plain
+    /// HelloWorld/
+    ///     file.world
+    /// Test.txt [missing]
+    /// ```
+    /// 
+    /// # Arguments
+    /// - `root`: The root of this directory (typically the `base` it was initialized with).
+    /// 
+    /// # Returns
+    /// The rendered tree, with one entry per line.
+    fn render(&self, root: impl AsRef<Path>) -> String {
+        let root: &Path = root.as_ref();
+        let mut entries: BTreeMap<PathBuf, (EntryKind, bool, Option<PathBuf>)> = BTreeMap::new();
+        let res: Result<(), std::convert::Infallible> = self.walk(&FieldSpec::default(), &mut |path: &Path, spec: &FieldSpec| {
+            let rel: &Path = path.strip_prefix(root).unwrap_or(path);
+            if rel.as_os_str().is_empty() { return Ok(()); }
+
+            // Find what's there
+            let (exists, target): (bool, Option<PathBuf>) = if spec.symlink.is_some() {
+                (fs::symlink_metadata(path).is_ok(), fs::read_link(path).ok())
+            } else {
+                (path.exists(), None)
+            };
+            let kind: EntryKind = match spec.kind {
+                EntryKind::Any if path.is_dir()  => EntryKind::Dir,
+                EntryKind::Any if path.is_file() => EntryKind::File,
+                kind                             => kind,
+            };
+            entries.insert(rel.into(), (kind, exists, target));
+
+            // Add any intermediate directories
+            if rel.is_relative() {
+                for ancestor in rel.ancestors().skip(1).filter(|a| !a.as_os_str().is_empty()) {
+                    entries.entry(ancestor.into()).or_insert_with(|| (EntryKind::Dir, root.join(ancestor).exists(), None));
+                }
+            }
+            Ok(())
+        });
+        if let Err(err) = res { match err {} }
+        render_tree(&entries)
+    }
+
+    /// Creates all paths in this directory that do not exist yet, leaving existing ones untouched.
+    /// 
+    /// Files that are created are filled with their default contents (see [`FieldSpec::contents`]), or left empty if they have none. Paths that are declared neither a file nor a directory (and have no default contents) are not created, since we don't know what they should be. On Unix, created paths with a declared [`FieldSpec::mode`] get exactly that mode (regardless of the umask).
+    /// 
+    /// Note that, like [`DirectoryExt::walk()`], this only considers paths that have been initialized. Dynamic entries will thus not be created (although the directories of the dynamic types in [`crate::std`] are), and neither will optional paths (since they are only initialized if they exist). Symlinks are not created either, since we don't know where they should point to.
+    /// 
+    /// Templates are rendered with an empty [`Context`]; use [`DirectoryExt::ensure_with()`] to give them any variables.
+    /// 
+    /// This creates paths on the ambient filesystem. For directories initialized with [`Directory::try_init_cap()`], use [`DirectoryExt::ensure_cap()`] instead.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files, or if we failed to render a template.
+    #[inline]
+    fn ensure(&self) -> Result<(), Error> { self.ensure_with(&Context::default()) }
+
+    /// Creates all paths in this directory that do not exist yet, leaving existing ones untouched, and renders any templates with the given context.
+    /// 
+    /// See [`DirectoryExt::ensure()`] for more information.
+    /// 
+    /// # Arguments
+    /// - `ctx`: The [`Context`] to render templates with.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files, or if we failed to render a template.
+    #[inline]
+    fn ensure_with(&self, ctx: &Context) -> Result<(), Error> { ensure_in(self, &Ambient, ctx) }
+
+    /// Creates all paths in this directory that do not exist yet on the given capability, leaving existing ones untouched.
+    /// 
+    /// This is like [`DirectoryExt::ensure()`], except that paths are interpreted relative to the capability instead of on the ambient filesystem. Use this for directories initialized with [`Directory::try_init_cap()`].
+    /// 
+    /// # Arguments
+    /// - `cap`: The [`Dir`](cap_std::fs::Dir) capability that the paths in this directory are relative to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files, or if we failed to render a template.
+    #[cfg(feature = "cap-std")]
+    #[inline]
+    fn ensure_cap(&self, cap: &cap_std::fs::Dir) -> Result<(), Error> { self.ensure_cap_with(cap, &Context::default()) }
+
+    /// Creates all paths in this directory that do not exist yet on the given capability, leaving existing ones untouched, and renders any templates with the given context.
+    /// 
+    /// See [`DirectoryExt::ensure_cap()`] for more information.
+    /// 
+    /// # Arguments
+    /// - `cap`: The [`Dir`](cap_std::fs::Dir) capability that the paths in this directory are relative to.
+    /// - `ctx`: The [`Context`] to render templates with.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files, or if we failed to render a template.
+    #[cfg(feature = "cap-std")]
+    #[inline]
+    fn ensure_cap_with(&self, cap: &cap_std::fs::Dir, ctx: &Context) -> Result<(), Error> { ensure_in(self, cap, ctx) }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-test-directory-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn test_indexmap() {
+        let root: PathBuf = scratch("indexmap");
+        for name in ["c", "a", "b"] { fs::write(root.join(name), "").unwrap(); }
+
+        // Entries are scanned in order of path
+        let mut entries: IndexMap<PathBuf, PathBuf> = IndexMap::try_init(&root).unwrap();
+        assert_eq!(entries.keys().collect::<Vec<&PathBuf>>(), [&root.join("a"), &root.join("b"), &root.join("c")]);
+        assert!(entries.exists());
+        assert!(<IndexMap<PathBuf, PathBuf> as crate::schema::Schema>::schema().dynamic);
+
+        // But entries inserted later keep their insertion order, also when walking
+        entries.insert(root.join("0"), root.join("0"));
+        let mut visited: Vec<PathBuf> = vec![];
+        let res: Result<(), std::convert::Infallible> = entries.walk(&FieldSpec::default(), &mut |path: &Path, _: &FieldSpec| {
+            visited.push(path.into());
+            Ok(())
+        });
+        if let Err(err) = res { match err {} }
+        assert_eq!(visited, [root.join("a"), root.join("b"), root.join("c"), root.join("0")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_confined_parent() {
+        #[derive(crate::Directory)]
+        struct Layout {
+            #[file(path = "../secret.txt")]
+            secret: PathBuf,
+        }
+
+        let root: PathBuf = scratch("confined-parent");
+        fs::create_dir(root.join("layout")).unwrap();
+        assert!(matches!(Layout::try_init_confined(root.join("layout")), Err(Error::Escape { path, .. }) if path == root.join("layout/../secret.txt")));
+        assert!(Layout::try_init(root.join("layout")).is_ok());
+
+        // `..` that stays inside is fine
+        #[derive(crate::Directory)]
+        struct Inside {
+            #[file(path = "nested/../secret.txt")]
+            secret: PathBuf,
+        }
+        assert!(Inside::try_init_confined(root.join("layout")).is_ok());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_confined_ignored() {
+        /// Only implements [`Directory::try_init()`], and thus ignores the options.
+        struct Parent(PathBuf);
+        impl Directory for Parent {
+            type Error = Error;
+
+            #[inline]
+            fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Ok(Self(base.into().join(".."))) }
+        }
+        impl DirectoryExt for Parent {
+            #[inline]
+            fn exists(&self) -> bool { self.0.exists() }
+
+            #[inline]
+            fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> { visit(&self.0, spec) }
+        }
+
+        // Initializing doesn't check anything, but the result is still checked afterwards
+        let root: PathBuf = scratch("confined-ignored");
+        assert!(matches!(Parent::try_init_confined(&root), Err(Error::Escape { path, .. }) if path == root.join("..")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_confined_absolute() {
+        #[derive(crate::Directory)]
+        struct Layout {
+            #[dir(path = "/")]
+            system: PathBuf,
+        }
+
+        let root: PathBuf = scratch("confined-absolute");
+        assert!(matches!(Layout::try_init_confined(&root), Err(Error::Escape { .. })));
+        assert!(Layout::try_init(&root).is_ok());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "derive", unix))]
+    #[test]
+    fn test_confined_symlink() {
+        use std::os::unix::fs::symlink;
+
+        use crate::std::Dynamic;
+
+        #[derive(crate::Directory)]
+        struct Layout {
+            #[dir]
+            data: PathBuf,
+            #[dir]
+            plugins: Dynamic<PathBuf>,
+            #[dir(symlink)]
+            current: PathBuf,
+        }
+
+        let root: PathBuf = scratch("confined-symlink");
+        let outside: PathBuf = root.join("outside");
+        let layout: PathBuf = root.join("layout");
+        fs::create_dir_all(&outside).unwrap();
+        fs::create_dir_all(layout.join("data")).unwrap();
+        fs::create_dir_all(layout.join("plugins/a")).unwrap();
+        symlink(layout.join("data"), layout.join("current")).unwrap();
+        assert!(Layout::try_init_confined(&layout).is_ok());
+
+        // A symlink to outside in place of a fixed field is caught...
+        fs::remove_dir(layout.join("data")).unwrap();
+        symlink(&outside, layout.join("data")).unwrap();
+        assert!(matches!(Layout::try_init_confined(&layout), Err(Error::Escape { path, .. }) if path == layout.join("data")));
+        fs::remove_file(layout.join("data")).unwrap();
+        fs::create_dir(layout.join("data")).unwrap();
+
+        // ...as is one among the dynamic entries...
+        symlink(&outside, layout.join("plugins/b")).unwrap();
+        assert!(matches!(Layout::try_init_confined(&layout), Err(Error::Escape { path, .. }) if path == layout.join("plugins/b")));
+        fs::remove_file(layout.join("plugins/b")).unwrap();
+
+        // ...and a declared symlink, even if it may point anywhere otherwise
+        fs::remove_file(layout.join("current")).unwrap();
+        symlink(&outside, layout.join("current")).unwrap();
+        assert!(matches!(Layout::try_init_confined(&layout), Err(Error::Escape { path, .. }) if path == layout.join("current")));
+        assert!(Layout::try_init(&layout).is_ok());
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,93 @@
+//  EMBED.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:05:28
+//  Last edited:
+//    18 Oct 2026, 22:05:28
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the types that hold directory trees embedded in the binary
+//!   by `#[directories(embed = "...")]`, and that can extract them onto
+//!   disk again.
+// 
+
+use std::fs;
+use std::path::Path;
+
+use crate::directory::Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Writes the given contents to a file, unless it already exists.
+/// 
+/// Any missing parent directories are created too.
+/// 
+/// # Arguments
+/// - `path`: The path of the file to write.
+/// - `contents`: The contents to write.
+/// 
+/// # Errors
+/// This function errors if we failed to create the parent directories or write the file.
+pub fn extract_file(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    if path.exists() { return Ok(()); }
+    if let Some(parent) = path.parent() {
+        if let Err(err) = fs::create_dir_all(parent) { return Err(Error::DirCreate { path: parent.into(), err }); }
+    }
+    if let Err(err) = fs::write(path, contents) { return Err(Error::FileWrite { path: path.into(), err }); }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single file or directory that has been embedded in the binary.
+#[derive(Clone, Copy, Debug)]
+pub enum EmbeddedEntry {
+    /// It's a file with the given contents.
+    File(&'static [u8]),
+    /// It's a directory with the given entries.
+    Dir(EmbeddedDir),
+}
+impl EmbeddedEntry {
+    /// Writes this entry to disk, without overwriting anything that already exists.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to write the entry to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files.
+    pub fn extract(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        match self {
+            Self::File(contents) => extract_file(path.as_ref(), contents),
+            Self::Dir(dir)       => dir.extract(path),
+        }
+    }
+}
+
+
+
+/// Defines a directory that has been embedded in the binary, as a list of named entries.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedDir(pub &'static [(&'static str, EmbeddedEntry)]);
+impl EmbeddedDir {
+    /// Writes this directory and all of its entries to disk, without overwriting anything that already exists.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to write the directory to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the directories or files.
+    pub fn extract(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path: &Path = path.as_ref();
+        if let Err(err) = fs::create_dir_all(path) { return Err(Error::DirCreate { path: path.into(), err }); }
+        for (name, entry) in self.0 {
+            entry.extract(path.join(name))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+//  GLOB.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:37:03
+//  Last edited:
+//    18 Oct 2026, 22:37:03
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements glob patterns to filter the entries of dynamic fields by
+//!   name, as used by `#[dir(glob = ...)]` and
+//!   [`Glob<T, P>`](crate::std::Glob).
+//! 
+//!   Patterns are matched against the file name of every entry (not its
+//!   full path), and support `*` (any sequence of characters), `?` (any
+//!   single character) and character classes like `[abc]`, `[a-z]` or
+//!   `[!0-9]`. Patterns starting with `!` exclude entries instead.
+// 
+
+use std::ffi::OsStr;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Matches the given tokens against the given name.
+/// 
+/// # Arguments
+/// - `tokens`: The (remaining) tokens of a [`Pattern`].
+/// - `name`: The (remaining) characters of the name.
+/// 
+/// # Returns
+/// True if the tokens match the whole name, or false otherwise.
+fn matches(tokens: &[Token], name: &[char]) -> bool {
+    match tokens.split_first() {
+        None => name.is_empty(),
+        Some((Token::Star, rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+        Some((token, rest)) => match name.split_first() {
+            Some((c, name)) => token.matches(*c) && matches(rest, name),
+            None            => false,
+        },
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a single token in a [`Pattern`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    /// Matches exactly this character.
+    Literal(char),
+    /// Matches any single character (`?`).
+    Any,
+    /// Matches any sequence of characters (`*`).
+    Star,
+    /// Matches any character in (or, if negated, not in) the given ranges (`[...]`).
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+impl Token {
+    /// Returns whether this token matches the given character.
+    /// 
+    /// # Arguments
+    /// - `c`: The character to match.
+    /// 
+    /// # Returns
+    /// True if it does, or false otherwise. Always returns false for [`Token::Star`].
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Literal(l)                => *l == c,
+            Self::Any                       => true,
+            Self::Star                      => false,
+            Self::Class { negated, ranges } => ranges.iter().any(|(start, end)| (*start..=*end).contains(&c)) != *negated,
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single glob pattern that matches file names.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    /// The pattern as it was given.
+    source : String,
+    /// The parsed tokens.
+    tokens : Vec<Token>,
+}
+impl Pattern {
+    /// Constructor for the Pattern that parses it from the given string.
+    /// 
+    /// Parsing never fails; an unclosed character class (e.g., `[a`) is matched literally.
+    /// 
+    /// # Arguments
+    /// - `pattern`: The pattern to parse (e.g., `*.log`).
+    /// 
+    /// # Returns
+    /// A new Pattern instance.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let source: String = pattern.into();
+        let chars: Vec<char> = source.chars().collect();
+        let mut tokens: Vec<Token> = vec![];
+        let mut i: usize = 0;
+        while i < chars.len() {
+            match chars[i] {
+                // Collapse sequences of stars
+                '*' => if tokens.last() != Some(&Token::Star) { tokens.push(Token::Star); },
+                '?' => tokens.push(Token::Any),
+                '[' => if let Some(len) = chars[i + 1..].iter().skip(1).position(|c| *c == ']').map(|p| p + 1) {
+                    // Parse the class, where the first character may be a `]` itself
+                    let class: &[char] = &chars[i + 1..i + 1 + len];
+                    let (negated, class): (bool, &[char]) = match class.split_first() {
+                        Some(('!' | '^', rest)) if !rest.is_empty() => (true, rest),
+                        _ => (false, class),
+                    };
+                    let mut ranges: Vec<(char, char)> = vec![];
+                    let mut j: usize = 0;
+                    while j < class.len() {
+                        if j + 2 < class.len() && class[j + 1] == '-' {
+                            ranges.push((class[j], class[j + 2]));
+                            j += 3;
+                        } else {
+                            ranges.push((class[j], class[j]));
+                            j += 1;
+                        }
+                    }
+                    tokens.push(Token::Class { negated, ranges });
+                    i += len + 1;
+                } else {
+                    tokens.push(Token::Literal('['));
+                },
+                c => tokens.push(Token::Literal(c)),
+            }
+            i += 1;
+        }
+        Self { source, tokens }
+    }
+
+    /// Returns whether this pattern matches the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name to match. If it is not valid UTF-8, the invalid parts are never matched literally.
+    /// 
+    /// # Returns
+    /// True if the pattern matches the whole name, or false otherwise.
+    pub fn matches(&self, name: impl AsRef<OsStr>) -> bool {
+        let name: Vec<char> = name.as_ref().to_string_lossy().chars().collect();
+        matches(&self.tokens, &name)
+    }
+
+    /// Returns the pattern as it was given.
+    #[inline]
+    pub fn as_str(&self) -> &str { &self.source }
+}
+
+
+
+/// Defines a set of include and exclude [`Pattern`]s that together filter file names.
+/// 
+/// A name matches if it matches any of the include patterns (or if there are none), and none of the exclude patterns.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Patterns {
+    /// The patterns of which at least one must match.
+    include : Vec<Pattern>,
+    /// The patterns of which none may match.
+    exclude : Vec<Pattern>,
+}
+impl Patterns {
+    /// Constructor for the Patterns that parses them from the given strings.
+    /// 
+    /// # Arguments
+    /// - `patterns`: The patterns to parse. Those starting with `!` are exclude patterns (e.g., `!*.tmp`); the others are include patterns.
+    /// 
+    /// # Returns
+    /// A new Patterns instance.
+    pub fn new<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>) -> Self {
+        let mut res: Self = Self::default();
+        for pattern in patterns {
+            match pattern.as_ref().strip_prefix('!') {
+                Some(pattern) => res.exclude.push(Pattern::new(pattern)),
+                None          => res.include.push(Pattern::new(pattern.as_ref())),
+            }
+        }
+        res
+    }
+
+    /// Returns whether the given name passes these patterns.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name to match.
+    /// 
+    /// # Returns
+    /// True if it matches any of the include patterns (or there are none) and none of the exclude patterns, or false otherwise.
+    pub fn matches(&self, name: impl AsRef<OsStr>) -> bool {
+        let name: &OsStr = name.as_ref();
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(name))) && !self.exclude.iter().any(|p| p.matches(name))
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_wildcards() {
+        assert!(Pattern::new("*.log").matches("app.log"));
+        assert!(Pattern::new("*.log").matches(".log"));
+        assert!(!Pattern::new("*.log").matches("app.log.old"));
+        assert!(Pattern::new("a*b*c").matches("aXXbYYc"));
+        assert!(!Pattern::new("a*b*c").matches("aXXbYY"));
+        assert!(Pattern::new("**").matches(""));
+        assert!(Pattern::new("file?.txt").matches("file1.txt"));
+        assert!(!Pattern::new("file?.txt").matches("file.txt"));
+        assert!(Pattern::new("plain").matches("plain"));
+        assert!(!Pattern::new("plain").matches("plainer"));
+    }
+
+    #[test]
+    fn test_pattern_classes() {
+        assert!(Pattern::new("[abc]").matches("b"));
+        assert!(!Pattern::new("[abc]").matches("d"));
+        assert!(Pattern::new("v[0-9]").matches("v7"));
+        assert!(!Pattern::new("v[0-9]").matches("vx"));
+        assert!(Pattern::new("[!0-9]*").matches("x1"));
+        assert!(!Pattern::new("[^0-9]*").matches("1x"));
+        // A leading `]` is part of the class, and a lone `!` is not a negation
+        assert!(Pattern::new("[]a]").matches("]"));
+        assert!(Pattern::new("[!]").matches("!"));
+        // Unclosed classes are matched literally
+        assert!(Pattern::new("[a").matches("[a"));
+        assert!(!Pattern::new("[a").matches("a"));
+    }
+
+    #[test]
+    fn test_patterns() {
+        let patterns: Patterns = Patterns::new(["*.rs", "*.toml", "!build.rs"]);
+        assert!(patterns.matches("main.rs"));
+        assert!(patterns.matches("Cargo.toml"));
+        assert!(!patterns.matches("build.rs"));
+        assert!(!patterns.matches("README.md"));
+
+        // Without include patterns, everything not excluded matches
+        let patterns: Patterns = Patterns::new(["!*.tmp"]);
+        assert!(patterns.matches("anything"));
+        assert!(!patterns.matches("file.tmp"));
+        assert!(Patterns::default().matches("anything"));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_validate_glob() {
+        use std::fs;
+        use std::path::PathBuf;
+
+        use crate::directory::{Directory as _, DirectoryExt as _, Problem};
+        use crate::schema::EntryKind;
+        use crate::std::Dynamic;
+
+        #[derive(crate::Directory)]
+        struct Logs {
+            #[dir(glob = "*.log")]
+            logs: Dynamic<PathBuf>,
+        }
+
+        let root: PathBuf = std::env::temp_dir().join(format!("directories-test-glob-validate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("logs")).unwrap();
+        for name in ["a.log", "b.log", "notes.txt"] { fs::write(root.join("logs").join(name), "").unwrap(); }
+
+        // Only the matching entries are collected, and the others aren't a problem
+        let logs: Logs = Logs::try_init(&root).unwrap();
+        assert_eq!(logs.logs.len(), 2);
+        assert!(logs.logs.contains_key(&root.join("logs/a.log")));
+        assert!(!logs.logs.contains_key(&root.join("logs/notes.txt")));
+        assert!(logs.validate().is_empty());
+
+        // The directory itself is still required, though
+        fs::remove_dir_all(root.join("logs")).unwrap();
+        let logs: Logs = Logs::try_init(&root).unwrap();
+        assert!(matches!(logs.validate().as_slice(), [Problem::Missing { path, kind: EntryKind::Dir }] if *path == root.join("logs")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,654 @@
+//  LAYOUT.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:57:49
+//  Last edited:
+//    18 Oct 2026, 22:29:20
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the [`DynamicLayout`], which is a [`Directory`] whose
+//!   layout is loaded at runtime from a TOML/YAML specification instead
+//!   of derived at compile time.
+// 
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs;
+use std::ops::Index;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "cap-std")]
+use crate::directory::check_exists;
+use crate::directory::{scan, Directory, DirectoryExt, Error as DirError, FieldSpec, InitOptions};
+use crate::naming::{deduce_path, default_exts, parse_ext};
+use crate::schema::{EntryKind, EntrySchema, FieldSchema, LayoutSchema, Schema};
+
+
+/***** CONSTANTS *****/
+/// The names of the files that [`DynamicLayout`] looks for when it is initialized as a [`Directory`], in order of preference.
+pub const SPEC_FILES: [ &str; 3 ] = [ ".layout.toml", ".layout.yaml", ".layout.yml" ];
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that may occur when loading layout specifications.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read the specification file.
+    Read { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the specification as TOML.
+    Toml { err: toml::de::Error },
+    /// Failed to parse the specification as YAML.
+    Yaml { err: serde_yaml::Error },
+    /// Failed to parse the specification as JSON.
+    #[cfg(feature = "json")]
+    Json { err: serde_json::Error },
+    /// The format of the specification could not be deduced from its path.
+    UnknownFormat { path: PathBuf },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Read { path, .. }    => write!(f, "Failed to read layout specification '{}'", path.display()),
+            Toml { .. }          => write!(f, "Failed to parse layout specification as TOML"),
+            Yaml { .. }          => write!(f, "Failed to parse layout specification as YAML"),
+            #[cfg(feature = "json")]
+            Json { .. }          => write!(f, "Failed to parse layout specification as JSON"),
+            UnknownFormat { path } => write!(f, "Cannot deduce format of layout specification '{}' (expected a '.toml', '.yaml' or '.yml' file)", path.display()),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use Error::*;
+        match self {
+            Read { err, .. }    => Some(err),
+            Toml { err }        => Some(err),
+            Yaml { err }        => Some(err),
+            #[cfg(feature = "json")]
+            Json { err }        => Some(err),
+            UnknownFormat { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given path exists as the given kind of entry.
+/// 
+/// # Arguments
+/// - `path`: The path to check.
+/// - `kind`: The [`EntryKind`] that it should be.
+/// 
+/// # Returns
+/// True if it exists and is of the correct kind, or false otherwise.
+#[inline]
+fn exists_as(path: &Path, kind: EntryKind) -> bool {
+    match kind {
+        EntryKind::File => path.is_file(),
+        EntryKind::Dir  => path.is_dir(),
+        EntryKind::Any  => path.exists(),
+    }
+}
+
+
+
+
+
+/***** SPECIFICATIONS *****/
+impl LayoutSchema {
+    /// Parses a layout specification from TOML.
+    /// 
+    /// The specification is simply a serialized [`LayoutSchema`], where any missing paths are deduced from the field names as `#[derive(Directory)]` would.
+    /// 
+    /// # Arguments
+    /// - `raw`: The raw TOML to parse.
+    /// 
+    /// # Returns
+    /// A new [`LayoutSchema`] that can be used to initialize [`DynamicLayout`]s.
+    /// 
+    /// # Errors
+    /// This function errors if the given TOML was not a valid specification.
+    #[inline]
+    pub fn from_toml(raw: impl AsRef<str>) -> Result<Self, Error> { toml::from_str(raw.as_ref()).map_err(|err| Error::Toml { err }) }
+
+    /// Parses a layout specification from YAML.
+    /// 
+    /// The specification is simply a serialized [`LayoutSchema`], where any missing paths are deduced from the field names as `#[derive(Directory)]` would.
+    /// 
+    /// # Arguments
+    /// - `raw`: The raw YAML to parse.
+    /// 
+    /// # Returns
+    /// A new [`LayoutSchema`] that can be used to initialize [`DynamicLayout`]s.
+    /// 
+    /// # Errors
+    /// This function errors if the given YAML was not a valid specification.
+    #[inline]
+    pub fn from_yaml(raw: impl AsRef<str>) -> Result<Self, Error> { serde_yaml::from_str(raw.as_ref()).map_err(|err| Error::Yaml { err }) }
+
+    /// Parses a layout specification from JSON, as written by [`LayoutSchema::to_json()`].
+    /// 
+    /// # Arguments
+    /// - `raw`: The raw JSON to parse.
+    /// 
+    /// # Returns
+    /// A new [`LayoutSchema`] that can be used to initialize [`DynamicLayout`]s.
+    /// 
+    /// # Errors
+    /// This function errors if the given JSON was not a valid specification.
+    #[cfg(feature = "json")]
+    #[inline]
+    pub fn from_json(raw: impl AsRef<str>) -> Result<Self, Error> { serde_json::from_str(raw.as_ref()).map_err(|err| Error::Json { err }) }
+
+    /// Loads a layout specification from a file, deducing its format from the extension (`.toml`, `.yaml`/`.yml` or, with the `json`-feature, `.json`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file to load.
+    /// 
+    /// # Returns
+    /// A new [`LayoutSchema`] that can be used to initialize [`DynamicLayout`]s.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the file, its format is unknown or it was not a valid specification.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Read the file
+        let raw: String = match fs::read_to_string(path) {
+            Ok(raw)  => raw,
+            Err(err) => { return Err(Error::Read { path: path.into(), err }); },
+        };
+
+        // Parse it according to the extension
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml")         => Self::from_toml(raw),
+            Some("yaml" | "yml") => Self::from_yaml(raw),
+            #[cfg(feature = "json")]
+            Some("json")         => Self::from_json(raw),
+            _                    => Err(Error::UnknownFormat { path: path.into() }),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A [`Directory`] of which the layout is given at runtime by a [`LayoutSchema`], e.g., loaded from a TOML or YAML specification.
+/// 
+/// Its fields can be accessed by name, either through [`DynamicLayout::get()`] or by indexing (e.g., `layout["hello_world"]`).
+/// 
+/// When initialized through [`Directory::init()`], the specification is read from one of the [`SPEC_FILES`] in the given directory. Use [`DynamicLayout::from_schema()`] to initialize it with a specification from elsewhere.
+#[derive(Clone, Debug)]
+pub struct DynamicLayout {
+    /// The path of the directory described by this layout.
+    path     : PathBuf,
+    /// The path of the lock file of this layout, if it declares any.
+    lockfile : Option<PathBuf>,
+    /// The fields in this layout, in the order they were specified.
+    fields   : Vec<(String, Entry)>,
+}
+
+impl DynamicLayout {
+    /// Initializes a DynamicLayout by deducing all paths from the given specification.
+    /// 
+    /// # Arguments
+    /// - `schema`: The [`LayoutSchema`] that describes the layout.
+    /// - `base`: A [`Path`] that defines the base for any relative paths in this directory.
+    /// 
+    /// # Returns
+    /// A new DynamicLayout with all of the fields properly initialized.
+    /// 
+    /// # Panics
+    /// This function panics if we failed to scan any dynamic fields.
+    #[inline]
+    pub fn from_schema(schema: &LayoutSchema, base: impl Into<PathBuf>) -> Self { Self::try_from_schema(schema, base).unwrap_or_else(|err| panic!("Failed to initialize DynamicLayout: {err}")) }
+
+    /// Initializes a DynamicLayout by deducing all paths from the given specification.
+    /// 
+    /// # Arguments
+    /// - `schema`: The [`LayoutSchema`] that describes the layout.
+    /// - `base`: A [`Path`] that defines the base for any relative paths in this directory.
+    /// 
+    /// # Returns
+    /// A new DynamicLayout with all of the fields properly initialized.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to scan any dynamic fields.
+    #[inline]
+    pub fn try_from_schema(schema: &LayoutSchema, base: impl Into<PathBuf>) -> Result<Self, DirError> { Self::try_from_schema_with(schema, base, &InitOptions::default()) }
+
+    /// Initializes a DynamicLayout by deducing all paths from the given specification, with the given options.
+    /// 
+    /// # Arguments
+    /// - `schema`: The [`LayoutSchema`] that describes the layout.
+    /// - `base`: A [`Path`] that defines the base for any relative paths in this directory.
+    /// - `opts`: The [`InitOptions`] that change how the layout is initialized.
+    /// 
+    /// # Returns
+    /// A new DynamicLayout with all of the fields properly initialized.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to scan any dynamic fields.
+    pub fn try_from_schema_with(schema: &LayoutSchema, base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, DirError> {
+        let base: PathBuf = base.into();
+
+        // Build the extensions database for this layout
+        let mut exts: Vec<(String, String)> = default_exts();
+        exts.extend(schema.exts.iter().map(|e| parse_ext(e)));
+
+        // Initialize all the fields
+        let mut fields: Vec<(String, Entry)> = Vec::with_capacity(schema.fields.len());
+        for field in &schema.fields {
+            // Resolve the path of the field
+            let path: PathBuf = match (&field.path, field.flatten) {
+                (Some(path), _) => base.join(path),
+                (None, true)    => base.clone(),
+                (None, false)   => base.join(deduce_path(&field.name, &exts)),
+            };
+            opts.check_confined(&path)?;
+
+            // Initialize it
+            fields.push((field.name.clone(), Entry::try_init(path, field, opts)?));
+        }
+
+        // Done
+        let lockfile: Option<PathBuf> = schema.lockfile.as_ref().map(|l| base.join(l));
+        Ok(Self { path: base, lockfile, fields })
+    }
+
+
+
+    /// Returns the path of the directory described by this layout.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the field with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the field to return.
+    /// 
+    /// # Returns
+    /// The [`Entry`] of the field, or [`None`] if there is no field with that name.
+    #[inline]
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&Entry> {
+        let name: &str = name.as_ref();
+        self.fields.iter().find_map(|(n, e)| if n == name { Some(e) } else { None })
+    }
+
+    /// Returns an iterator over the names and [`Entry`]s of all fields in this layout, in the order they were specified.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Entry)> { self.fields.iter().map(|(n, e)| (n.as_str(), e)) }
+}
+
+impl Directory for DynamicLayout {
+    type Error = DirError;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Find the specification in the directory
+        let path: PathBuf = match SPEC_FILES.iter().map(|f| base.join(f)).find(|p| p.is_file()) {
+            Some(path) => path,
+            None       => { return Err(DirError::SpecNotFound { path: base }); },
+        };
+        let schema: LayoutSchema = match LayoutSchema::from_path(&path) {
+            Ok(schema) => schema,
+            Err(err)   => { return Err(DirError::Spec { path, err }); },
+        };
+
+        // Initialize with it
+        Self::try_from_schema_with(&schema, base, opts)
+    }
+}
+impl DirectoryExt for DynamicLayout {
+    #[inline]
+    fn exists(&self) -> bool { self.fields.iter().all(|(_, e)| e.exists()) }
+
+    #[inline]
+    fn lockfile(&self) -> Option<PathBuf> { self.lockfile.clone() }
+
+    fn walk<E>(&self, _spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        for (_, entry) in &self.fields {
+            entry.walk(visit)?;
+        }
+        Ok(())
+    }
+}
+impl Schema for DynamicLayout {
+    /// Describes a DynamicLayout as a directory, since its fields are only known at runtime.
+    #[inline]
+    fn schema() -> EntrySchema { EntrySchema { kind: EntryKind::Dir, ..Default::default() } }
+}
+
+impl<S: AsRef<str>> Index<S> for DynamicLayout {
+    type Output = Entry;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: S) -> &Self::Output {
+        let index: &str = index.as_ref();
+        self.get(index).unwrap_or_else(|| panic!("No field '{index}' in DynamicLayout"))
+    }
+}
+
+
+
+/// Defines a single, initialized field in a [`DynamicLayout`].
+#[derive(Clone, Debug)]
+pub enum Entry {
+    /// It's a single file or directory.
+    Path(PathBuf, EntryKind),
+    /// It's a nested layout.
+    Layout(DynamicLayout),
+    /// It's an optional entry, which was only initialized if it exists.
+    Optional(Option<Box<Entry>>),
+    /// It's a directory (at the given path) of which the nested entries are scanned dynamically. Only those that exist are kept, sorted by path.
+    Dynamic(PathBuf, BTreeMap<PathBuf, Entry>),
+}
+impl Entry {
+    /// Initializes an entry from its specification.
+    /// 
+    /// # Arguments
+    /// - `path`: The resolved path of the entry.
+    /// - `field`: The [`FieldSchema`] that describes it.
+    /// - `opts`: The [`InitOptions`] that change how the entry is initialized.
+    /// 
+    /// # Returns
+    /// A new Entry.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to scan any dynamic fields.
+    fn try_init(path: PathBuf, field: &FieldSchema, opts: &InitOptions) -> Result<Self, DirError> {
+        // Unwrap the optional first
+        if field.optional {
+            if !opts.exists(&path) { return Ok(Self::Optional(None)); }
+            let field: FieldSchema = FieldSchema { optional: false, ..field.clone() };
+            return Ok(Self::Optional(Some(Box::new(Self::try_init(path, &field, opts)?))));
+        }
+
+        // Then scan dynamic fields
+        if field.dynamic {
+            let mut result: BTreeMap<PathBuf, Entry> = BTreeMap::new();
+            for entry_path in scan(&path, opts)? {
+                // Initialize it, only keeping those that exist (relative to the capability, if any)
+                let nested: Entry = Self::try_init_single(entry_path.clone(), field, opts)?;
+                #[cfg(feature = "cap-std")]
+                let exists: bool = match &opts.cap {
+                    Some(cap) => nested.walk(&mut |path: &Path, spec: &FieldSpec| check_exists(&**cap, path, spec)).is_ok(),
+                    None      => nested.exists(),
+                };
+                #[cfg(not(feature = "cap-std"))]
+                let exists: bool = nested.exists();
+                if exists { result.insert(entry_path, nested); }
+            }
+            return Ok(Self::Dynamic(path, result));
+        }
+
+        // Otherwise, it's just the one
+        Self::try_init_single(path, field, opts)
+    }
+
+    /// Initializes a single (i.e., non-optional, non-dynamic) entry from its specification.
+    /// 
+    /// # Arguments
+    /// - `path`: The resolved path of the entry.
+    /// - `field`: The [`FieldSchema`] that describes it.
+    /// - `opts`: The [`InitOptions`] that change how the entry is initialized.
+    /// 
+    /// # Returns
+    /// A new Entry.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to scan any dynamic fields in nested layouts.
+    fn try_init_single(path: PathBuf, field: &FieldSchema, opts: &InitOptions) -> Result<Self, DirError> {
+        match &field.nested {
+            Some(nested) => Ok(Self::Layout(DynamicLayout::try_from_schema_with(nested, path, opts)?)),
+            None         => Ok(Self::Path(path, field.kind)),
+        }
+    }
+
+
+
+    /// Returns whether this entry exists.
+    /// 
+    /// Like [`DirectoryExt::exists()`], this only checks mandatory paths, and checks that paths declared as files or directories are indeed files or directories.
+    /// 
+    /// # Returns
+    /// True if it does, false if it doesn't.
+    pub fn exists(&self) -> bool {
+        match self {
+            Self::Path(path, kind)       => exists_as(path, *kind),
+            Self::Layout(layout)         => layout.exists(),
+            Self::Optional(entry)        => entry.as_ref().map(|e| e.exists()).unwrap_or(true),
+            Self::Dynamic(_, entries)    => entries.values().all(|e| e.exists()),
+        }
+    }
+
+    /// Visits every path in this entry, like [`DirectoryExt::walk()`].
+    /// 
+    /// # Arguments
+    /// - `visit`: The closure to call for every path. If it errors, the walk is aborted.
+    /// 
+    /// # Errors
+    /// This function errors if `visit` errors.
+    pub fn walk<E>(&self, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        match self {
+            Self::Path(path, kind)       => visit(path, &FieldSpec::new(*kind)),
+            Self::Layout(layout)         => layout.walk(&FieldSpec::default(), visit),
+            Self::Optional(entry)        => entry.as_ref().map(|e| e.walk(visit)).unwrap_or(Ok(())),
+            Self::Dynamic(path, entries) => {
+                visit(path, &FieldSpec::new(EntryKind::Dir))?;
+                for entry in entries.values() {
+                    entry.walk(visit)?;
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Returns the path of this entry, if it has one.
+    /// 
+    /// # Returns
+    /// The path of the file, directory, nested layout or dynamic directory, or [`None`] if this is a missing optional entry.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Path(path, _)    => Some(path),
+            Self::Layout(layout)   => Some(layout.path()),
+            Self::Optional(entry)  => entry.as_ref().and_then(|e| e.path()),
+            Self::Dynamic(path, _) => Some(path),
+        }
+    }
+
+    /// Returns this entry as a nested layout, if it is one (or an optional one that exists).
+    #[inline]
+    pub fn as_layout(&self) -> Option<&DynamicLayout> {
+        match self {
+            Self::Layout(layout)  => Some(layout),
+            Self::Optional(entry) => entry.as_ref().and_then(|e| e.as_layout()),
+            _                     => None,
+        }
+    }
+
+    /// Returns the entries found in this dynamic entry, if it is one (or an optional one that exists).
+    #[inline]
+    pub fn as_dynamic(&self) -> Option<&BTreeMap<PathBuf, Entry>> {
+        match self {
+            Self::Dynamic(_, entries) => Some(entries),
+            Self::Optional(entry)     => entry.as_ref().and_then(|e| e.as_dynamic()),
+            _                         => None,
+        }
+    }
+}
+impl<S: AsRef<str>> Index<S> for Entry {
+    type Output = Entry;
+
+    /// Indexes the nested layout of this entry.
+    /// 
+    /// # Panics
+    /// This function panics if this entry is not a nested layout or the layout has no such field.
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: S) -> &Self::Output {
+        match self.as_layout() {
+            Some(layout) => &layout[index],
+            None         => panic!("Cannot index an Entry that is not a nested layout"),
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directory::Problem;
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-test-layout-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A specification with a deduced file, an optional file with a custom extension and a dynamic directory of nested layouts, as TOML.
+    const SPEC_TOML: &str = r#"
+name = "Workspace"
+exts = ["world"]
+lockfile = ".lock"
+
+[[fields]]
+name = "config_json"
+kind = "file"
+
+[[fields]]
+name = "save_world"
+kind = "file"
+optional = true
+
+[[fields]]
+name = "plugins"
+path = "plugins"
+kind = "dir"
+dynamic = true
+
+[fields.nested]
+[[fields.nested.fields]]
+name = "manifest_json"
+kind = "file"
+"#;
+    /// The same specification as [`SPEC_TOML`], as YAML.
+    const SPEC_YAML: &str = "
+name: Workspace
+exts: [world]
+lockfile: .lock
+fields:
+  - name: config_json
+    kind: file
+  - name: save_world
+    kind: file
+    optional: true
+  - name: plugins
+    path: plugins
+    kind: dir
+    dynamic: true
+    nested:
+      fields:
+        - name: manifest_json
+          kind: file
+";
+
+
+    #[test]
+    fn test_parse() {
+        let schema: LayoutSchema = LayoutSchema::from_toml(SPEC_TOML).unwrap();
+        assert_eq!(schema, LayoutSchema::from_yaml(SPEC_YAML).unwrap());
+        assert_eq!(schema.name.as_deref(), Some("Workspace"));
+        assert_eq!(schema.exts, vec!["world".to_string()]);
+        assert_eq!(schema.lockfile.as_deref(), Some(".lock"));
+
+        let names: Vec<&str> = schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["config_json", "save_world", "plugins"]);
+        assert_eq!((schema.fields[0].path.as_deref(), schema.fields[0].kind, schema.fields[0].optional), (None, EntryKind::File, false));
+        assert_eq!((schema.fields[1].kind, schema.fields[1].optional), (EntryKind::File, true));
+        assert!(schema.fields[2].dynamic);
+        assert_eq!(schema.fields[2].nested.as_ref().unwrap().fields[0].name, "manifest_json");
+
+        // Bad specifications are reported in their own format
+        assert!(matches!(LayoutSchema::from_toml("fields = 42"), Err(Error::Toml { .. })));
+        assert!(matches!(LayoutSchema::from_yaml("fields: 42"), Err(Error::Yaml { .. })));
+    }
+
+    #[test]
+    fn test_from_path() {
+        let root: PathBuf = scratch("from_path");
+        fs::write(root.join("spec.toml"), SPEC_TOML).unwrap();
+        fs::write(root.join("spec.yml"), SPEC_YAML).unwrap();
+        fs::write(root.join("spec.txt"), SPEC_TOML).unwrap();
+
+        assert_eq!(LayoutSchema::from_path(root.join("spec.toml")).unwrap(), LayoutSchema::from_path(root.join("spec.yml")).unwrap());
+        assert!(matches!(LayoutSchema::from_path(root.join("spec.txt")), Err(Error::UnknownFormat { .. })));
+        assert!(matches!(LayoutSchema::from_path(root.join("spec.yaml")), Err(Error::Read { .. })));
+
+        // Without any of the SPEC_FILES, there's nothing to initialize from
+        assert!(matches!(DynamicLayout::try_init(&root), Err(DirError::SpecNotFound { path }) if path == root));
+        fs::write(root.join(".layout.toml"), "fields = 42").unwrap();
+        assert!(matches!(DynamicLayout::try_init(&root), Err(DirError::Spec { err: Error::Toml { .. }, .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_init_validate() {
+        for (spec, raw) in [(".layout.toml", SPEC_TOML), (".layout.yaml", SPEC_YAML)] {
+            let root: PathBuf = scratch(&format!("init_validate{spec}"));
+            fs::write(root.join(spec), raw).unwrap();
+            fs::create_dir_all(root.join("plugins/a")).unwrap();
+            fs::create_dir_all(root.join("plugins/b")).unwrap();
+            fs::write(root.join("plugins/a/manifest.json"), "{}").unwrap();
+
+            // The optional file is missing, the required one is reported and only complete plugins are found
+            let layout: DynamicLayout = DynamicLayout::try_init(&root).unwrap();
+            assert_eq!(layout.path(), root);
+            assert_eq!(layout["config_json"].path(), Some(root.join("config.json").as_path()));
+            assert!(matches!(layout["save_world"], Entry::Optional(None)));
+            let plugins: Vec<&PathBuf> = layout["plugins"].as_dynamic().unwrap().keys().collect();
+            assert_eq!(plugins, [&root.join("plugins/a")]);
+            assert_eq!(layout["plugins"].as_dynamic().unwrap()[&root.join("plugins/a")]["manifest_json"].path(), Some(root.join("plugins/a/manifest.json").as_path()));
+            assert!(!layout.exists());
+            assert!(matches!(layout.validate().as_slice(), [Problem::Missing { path, kind: EntryKind::File }] if *path == root.join("config.json")));
+
+            // Once it's there, the layout is complete, and the optional file (deduced with the custom extension) is picked up
+            fs::write(root.join("config.json"), "{}").unwrap();
+            fs::write(root.join("save.world"), "").unwrap();
+            let layout: DynamicLayout = DynamicLayout::try_init(&root).unwrap();
+            assert_eq!(layout["save_world"].path(), Some(root.join("save.world").as_path()));
+            assert!(layout.exists());
+            assert!(layout.validate().is_empty());
+
+            // Declared kinds are checked too
+            fs::remove_file(root.join("config.json")).unwrap();
+            fs::create_dir(root.join("config.json")).unwrap();
+            assert!(matches!(layout.validate().as_slice(), [Problem::WrongKind { path, .. }] if *path == root.join("config.json")));
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    20 Apr 2023, 19:07:02
 //  Last edited:
-//    25 Jun 2023, 10:59:40
+//    18 Oct 2026, 22:42:59
 //  Auto updated?
 //    Yes
 // 
@@ -14,12 +14,36 @@
 // 
 
 // Declare the submodules
+pub mod atomic;
+#[cfg(feature = "cap-std")]
+pub mod cap;
+#[cfg(feature = "regex")]
+pub mod captures;
 mod directory;
+pub mod embed;
+pub mod glob;
+#[cfg(feature = "layout")]
+pub mod layout;
+pub mod naming;
+pub mod schema;
 pub mod std;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+#[cfg(feature = "tempfile")]
+pub mod temp;
+pub mod template;
+pub mod testing;
 
 // Push some of that in the crate namespace
-pub use directory::{Directory, DirectoryExt, Error};
+#[cfg(feature = "lock")]
+pub use directory::LayoutLock;
+pub use directory::{Contents, Directory, DirectoryExt, Error, FieldSpec, InitOptions, KeyPolicy, LinkTarget, NamePolicy, Problem, SymlinkPolicy};
+pub use schema::Schema;
 
 // Use the derive macros
 #[cfg(feature = "derive")]
 pub use directories_derive::*;
+
+// Allow the derive macros to refer to this crate in its own tests
+#[cfg(test)]
+extern crate self as directories;
@@ -0,0 +1,122 @@
+//  NAMING.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:55:57
+//  Last edited:
+//    18 Oct 2026, 21:55:57
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the naming convention that `#[derive(Directory)]` uses to
+//!   deduce paths from field names (e.g., `test_txt` -> `test.txt`).
+// 
+
+
+/***** CONSTANTS *****/
+/// Defines the list of extensions that we automatically recognize.
+/// 
+/// Note that this list is mirrored in the `directories-derive` crate, which cannot depend on this one. Keep them in sync!
+pub const EXTENSIONS: [ &str; 32 ] = [
+    // Executable files
+    "out", "exe",
+
+    // Source files
+    "rs", "c", "cpp", "cxx", "h", "hpp", "hxx", "py", "lua", "java", "R", "md", "html", "css", "js", "ts",
+
+    // Configuration files
+    "json", "yaml", "yml", "cfg",
+
+    // Data files
+    "csv", "pickle", "dat",
+
+    // Images
+    "jpg", "jpeg", "png", "gif", "svg", "tiff",
+
+    // Miscellaneous
+    "txt",
+];
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the database of suffixes and the extensions they map to, as used by the derive macro when no `#[directories(ext = ...)]` is given.
+/// 
+/// # Returns
+/// A list of field name suffixes (e.g., `_txt`) and the extensions they represent (e.g., `.txt`), in the order of [`EXTENSIONS`].
+#[inline]
+pub fn default_exts() -> Vec<(String, String)> {
+    EXTENSIONS.iter().map(|e| (format!("_{e}"), format!(".{e}"))).collect()
+}
+
+/// Parses an extension specification as given to `#[directories(ext = ...)]`.
+/// 
+/// This is either a plain extension (e.g., `world`), which maps `_world` to `.world`, or an explicit `<suffix>:<extension>` pair (e.g., `_world:.world`).
+/// 
+/// # Arguments
+/// - `spec`: The extension specification to parse.
+/// 
+/// # Returns
+/// A tuple of the field name suffix and the extension it maps to.
+pub fn parse_ext(spec: &str) -> (String, String) {
+    match spec.find(':') {
+        Some(pos) => (spec[..pos].into(), spec[pos + 1..].into()),
+        None      => (format!("_{spec}"), format!(".{spec}")),
+    }
+}
+
+/// Deduces the path of a field from its name, exactly like the derive macro does when no `#[file(path = ...)]` or `#[dir(path = ...)]` is given.
+/// 
+/// If multiple suffixes match, the longest one wins (e.g., `_tar_gz` over `_gz`). If the same suffix is given multiple times, the last one wins, so custom extensions can simply be appended to the [`default_exts()`].
+/// 
+/// # Arguments
+/// - `name`: The name of the field.
+/// - `exts`: The list of suffixes and extensions to apply (see [`default_exts()`]).
+/// 
+/// # Returns
+/// The path of the field, relative to its parent.
+pub fn deduce_path(name: &str, exts: &[(String, String)]) -> String {
+    // Find the longest suffix that the name ends in
+    let mut best: Option<&(String, String)> = None;
+    for ext in exts {
+        if name.ends_with(ext.0.as_str()) && best.map(|(suffix, _)| ext.0.len() >= suffix.len()).unwrap_or(true) {
+            best = Some(ext);
+        }
+    }
+
+    // Replace it with its extension, if any
+    match best {
+        Some((suffix, ext)) => format!("{}{ext}", &name[..name.len() - suffix.len()]),
+        None                => name.into(),
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduce_path() {
+        let mut exts: Vec<(String, String)> = default_exts();
+        assert_eq!(deduce_path("config_json", &exts), "config.json");
+        assert_eq!(deduce_path("results", &exts), "results");
+
+        // The longest suffix wins, regardless of the order, and later ones override earlier ones
+        exts.push(parse_ext("_tar_gz:.tar.gz"));
+        exts.push(parse_ext("gz"));
+        assert_eq!(deduce_path("data_tar_gz", &exts), "data.tar.gz");
+        exts.reverse();
+        assert_eq!(deduce_path("data_tar_gz", &exts), "data.tar.gz");
+        assert_eq!(deduce_path("data_gz", &exts), "data.gz");
+        exts.push(parse_ext("_json:.jsonc"));
+        assert_eq!(deduce_path("config_json", &exts), "config.jsonc");
+    }
+}
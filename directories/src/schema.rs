@@ -0,0 +1,446 @@
+//  SCHEMA.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 21:53:44
+//  Last edited:
+//    18 Oct 2026, 22:45:10
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the [`Schema`] trait, which describes the layout encoded
+//!   by a [`Directory`](crate::Directory) without having to initialize
+//!   it.
+// 
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "regex")]
+use crate::std::Matched;
+#[cfg(feature = "cap-std")]
+use crate::std::Opened;
+use crate::std::{ByName, Dynamic, Glob, Keyed, Named, Recursive, SortedDynamic, Symlink};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns whether the given boolean is false. Used to skip serializing default flags.
+#[cfg(feature = "serde")]
+#[inline]
+fn is_false(value: &bool) -> bool { !*value }
+
+
+
+
+
+/***** DEFAULT IMPLEMENTATIONS *****/
+// Default implementation for the [`PathBuf`], which may be either a file or a directory.
+impl Schema for PathBuf {
+    #[inline]
+    fn schema() -> EntrySchema { EntrySchema::default() }
+}
+
+// Default implementation for the [`Option<impl Schema>`], which marks the nested schema as optional.
+impl<T: Schema> Schema for Option<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.optional = true;
+        schema
+    }
+}
+
+// Default implementation for the [`HashMap<PathBuf, impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for HashMap<PathBuf, T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`BTreeMap<PathBuf, impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`IndexMap<PathBuf, impl Schema>`], which marks the nested schema as dynamic.
+#[cfg(feature = "indexmap")]
+impl<T: Schema> Schema for IndexMap<PathBuf, T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Vec<impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for Vec<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Vec<(PathBuf, impl Schema)>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for Vec<(PathBuf, T)> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Dynamic<impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for Dynamic<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`SortedDynamic<impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for SortedDynamic<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`ByName<impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for ByName<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Named<impl Schema>`], which marks the nested schema as dynamic.
+impl<T: Schema> Schema for Named<T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Glob<impl Schema, P>`], which marks the nested schema as dynamic.
+impl<T: Schema, P> Schema for Glob<T, P> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Recursive<impl Schema, R>`], which marks the nested schema as dynamic.
+impl<T: Schema, R> Schema for Recursive<T, R> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Matched<impl Schema, K>`], which marks the nested schema as dynamic.
+#[cfg(feature = "regex")]
+impl<T: Schema, K> Schema for Matched<T, K> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Keyed<K, impl Schema>`], which marks the nested schema as dynamic.
+impl<K, T: Schema> Schema for Keyed<K, T> {
+    #[inline]
+    fn schema() -> EntrySchema {
+        let mut schema: EntrySchema = T::schema();
+        schema.dynamic = true;
+        schema
+    }
+}
+
+// Default implementation for the [`Symlink`], which may point to either a file or a directory.
+impl Schema for Symlink {
+    #[inline]
+    fn schema() -> EntrySchema { EntrySchema::default() }
+}
+
+// Default implementation for the [`Opened<impl Schema>`], which is transparent.
+#[cfg(feature = "cap-std")]
+impl<T: Schema> Schema for Opened<T> {
+    #[inline]
+    fn schema() -> EntrySchema { T::schema() }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines what kind of entry on disk something refers to.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize), serde(rename_all = "lowercase"))]
+pub enum EntryKind {
+    /// It's a file.
+    File,
+    /// It's a directory.
+    Dir,
+    /// It may be either (e.g., a plain [`PathBuf`] of which we don't know more).
+    #[default]
+    Any,
+}
+
+
+
+/// Describes what a type looks like when it is used as a field in a layout.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EntrySchema {
+    /// The kind of entry on disk. If `dynamic` is true, this describes every entry found instead.
+    pub kind     : EntryKind,
+    /// Whether the entry may be missing.
+    pub optional : bool,
+    /// Whether the entry is a directory of which the nested entries are scanned dynamically.
+    pub dynamic  : bool,
+    /// The nested layout, if this entry is itself a layout. If `dynamic` is true, this describes every entry found instead.
+    pub nested   : Option<LayoutSchema>,
+}
+
+
+
+/// Describes the fields of a layout, i.e., something that `#[derive(Directory)]`s.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct LayoutSchema {
+    /// The name of the type that defines this layout, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub name     : Option<String>,
+    /// The doc comment of the type that defines this layout, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub doc      : Option<String>,
+    /// Any additional extensions to recognize when deducing paths from field names, like `#[directories(ext = ...)]`. Derived layouts always have their paths resolved, so leave this empty.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub exts     : Vec<String>,
+    /// The path of the lock file used to lock the layout, relative to it, like `#[directories(lockfile = ...)]`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub lockfile : Option<String>,
+    /// The fields in this layout.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fields   : Vec<FieldSchema>,
+}
+impl LayoutSchema {
+    /// Returns the layout of the given [`Schema`], if it is a layout.
+    /// 
+    /// # Generic arguments
+    /// - `T`: The [`Schema`] to return the layout of.
+    /// 
+    /// # Returns
+    /// The [`LayoutSchema`] of `T`, or [`None`] if `T` does not describe a layout (e.g., a [`PathBuf`]).
+    #[inline]
+    pub fn of<T: Schema>() -> Option<Self> { T::schema().nested }
+
+    /// Serializes this layout as JSON.
+    /// 
+    /// # Returns
+    /// A [`String`] with the JSON representation of this layout.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // Serializing plain structs and strings cannot fail
+        serde_json::to_string(self).unwrap_or_else(|err| panic!("Failed to serialize LayoutSchema as JSON: {err}"))
+    }
+
+    /// Serializes this layout as human-readable (i.e., indented) JSON.
+    /// 
+    /// # Returns
+    /// A [`String`] with the JSON representation of this layout.
+    #[cfg(feature = "json")]
+    pub fn to_json_pretty(&self) -> String {
+        // Serializing plain structs and strings cannot fail
+        serde_json::to_string_pretty(self).unwrap_or_else(|err| panic!("Failed to serialize LayoutSchema as JSON: {err}"))
+    }
+}
+
+
+
+/// Describes a single field in a [`LayoutSchema`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct FieldSchema {
+    /// The name of the field.
+    pub name     : String,
+    /// The path of the field, relative to the layout it is in (unless absolute). Is [`None`] if the field is flattened, i.e., refers to the layout's path itself, or if it should be deduced from the name (see [`crate::naming::deduce_path()`]).
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub path     : Option<String>,
+    /// Whether the field is flattened (`#[dir(flatten)]`) or refers to the layout itself (`#[this]`).
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub flatten  : bool,
+
+    /// The kind of entry on disk. If `dynamic` is true, this describes every entry found instead.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind     : EntryKind,
+    /// Whether the field may be missing.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub optional : bool,
+    /// Whether the field is a directory of which the nested entries are scanned dynamically.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_false"))]
+    pub dynamic  : bool,
+
+    /// The doc comment of the field, if any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub doc      : Option<String>,
+    /// The nested layout, if this field is itself a layout. If `dynamic` is true, this describes every entry found instead.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub nested   : Option<LayoutSchema>,
+}
+impl FieldSchema {
+    /// Constructor for the FieldSchema that combines a field's name and path with the [`EntrySchema`] of its type.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the field.
+    /// - `path`: The path of the field, or [`None`] if it is flattened.
+    /// - `entry`: The [`EntrySchema`] of the field's type.
+    /// 
+    /// # Returns
+    /// A new FieldSchema instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, path: Option<String>, entry: EntrySchema) -> Self {
+        let EntrySchema { kind, optional, dynamic, nested } = entry;
+        Self {
+            name : name.into(),
+            flatten : path.is_none(),
+            path,
+
+            kind,
+            optional,
+            dynamic,
+
+            doc : None,
+            nested,
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A Schema describes the layout encoded by a [`Directory`](crate::Directory) without having to initialize it.
+/// 
+/// Layouts that `#[derive(Directory)]` implement it when they opt in with `#[directories(schema)]`, which requires the types of all their fields to implement it too (i.e., nested layouts have to opt in as well). It can be used to communicate the layout to tools that are not written in Rust (e.g., as JSON when the `json`-feature is enabled).
+pub trait Schema {
+    /// Describes what this type looks like when used as a field in a layout.
+    /// 
+    /// # Returns
+    /// An [`EntrySchema`] that describes this type. For derived layouts, its `nested` field contains the [`LayoutSchema`].
+    fn schema() -> EntrySchema;
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+
+    /// A plugin.
+    #[derive(crate::Directory)]
+    #[directories(schema)]
+    struct Plugin {
+        #[file]
+        manifest_json: PathBuf,
+    }
+
+    /// The workspace.
+    #[derive(crate::Directory)]
+    #[directories(schema, lockfile = ".lock")]
+    struct Workspace {
+        #[this]
+        root: PathBuf,
+        /// The settings.
+        settings_json: Option<PathBuf>,
+        #[dir(path = "plugins")]
+        plugins: Dynamic<Plugin>,
+    }
+
+
+    #[test]
+    fn test_derived_schema() {
+        let plugin: LayoutSchema = LayoutSchema {
+            name     : Some("Plugin".into()),
+            doc      : Some("A plugin.".into()),
+            exts     : vec![],
+            lockfile : None,
+            fields   : vec![FieldSchema { kind: EntryKind::File, ..FieldSchema::new("manifest_json", Some("manifest.json".into()), EntrySchema::default()) }],
+        };
+        assert_eq!(Plugin::schema(), EntrySchema { kind: EntryKind::Dir, optional: false, dynamic: false, nested: Some(plugin.clone()) });
+
+        let mut root: FieldSchema = FieldSchema::new("root", None, EntrySchema::default());
+        root.kind = EntryKind::Dir;
+        let mut settings: FieldSchema = FieldSchema::new("settings_json", Some("settings.json".into()), EntrySchema { optional: true, ..Default::default() });
+        settings.kind = EntryKind::File;
+        settings.doc = Some("The settings.".into());
+        let plugins: FieldSchema = FieldSchema::new("plugins", Some("plugins".into()), EntrySchema { kind: EntryKind::Dir, optional: false, dynamic: true, nested: Some(plugin) });
+        assert_eq!(LayoutSchema::of::<Workspace>(), Some(LayoutSchema {
+            name     : Some("Workspace".into()),
+            doc      : Some("The workspace.".into()),
+            exts     : vec![],
+            lockfile : Some(".lock".into()),
+            fields   : vec![root, settings, plugins],
+        }));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_derived_schema_json() {
+        let json: serde_json::Value = serde_json::from_str(&LayoutSchema::of::<Workspace>().unwrap().to_json()).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "name": "Workspace",
+            "doc": "The workspace.",
+            "lockfile": ".lock",
+            "fields": [
+                { "name": "root", "flatten": true, "kind": "dir" },
+                { "name": "settings_json", "path": "settings.json", "kind": "file", "optional": true, "doc": "The settings." },
+                { "name": "plugins", "path": "plugins", "kind": "dir", "dynamic": true, "nested": {
+                    "name": "Plugin",
+                    "doc": "A plugin.",
+                    "fields": [{ "name": "manifest_json", "path": "manifest.json", "kind": "file" }],
+                } },
+            ],
+        }));
+
+        // The JSON can be read back as the same layout
+        let schema: LayoutSchema = serde_json::from_value(json).unwrap();
+        assert_eq!(Some(schema), LayoutSchema::of::<Workspace>());
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    24 Jun 2023, 13:52:10
 //  Last edited:
-//    25 Jun 2023, 12:06:19
+//    18 Oct 2026, 22:45:10
 //  Auto updated?
 //    Yes
 // 
@@ -13,57 +13,84 @@
 //!   [`Directory`].
 // 
 
-use std::collections::HashMap;
-use std::fs::{self, DirEntry, ReadDir};
+use std::collections::{btree_map, BTreeMap, HashMap, HashSet};
+use std::error;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::fs;
 use std::io::ErrorKind;
-use std::ops::{Deref, DerefMut};
-use std::path::PathBuf;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Index};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+#[cfg(feature = "cap-std")]
+use std::sync::Arc;
+
+#[cfg(feature = "cap-std")]
+use cap_std::ambient_authority;
+#[cfg(feature = "cap-std")]
+use cap_std::fs::Dir;
+
+#[cfg(feature = "regex")]
+use crate::captures::{Captures, FromCaptures};
+#[cfg(feature = "cap-std")]
+use crate::directory::Problem;
+use crate::directory::{scan, Directory, DirectoryExt, Error, FieldSpec, InitOptions, KeyPolicy, LinkTarget, NamePolicy};
+use crate::glob::Patterns;
+use crate::schema::EntryKind;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the [`FieldSpec`] with which a dynamic type visits its own directory.
+/// 
+/// # Arguments
+/// - `spec`: The [`FieldSpec`] with which the dynamic type has been declared in its parent.
+/// 
+/// # Returns
+/// The same spec, except that it always declares a directory (without contents).
+#[inline]
+fn dir_spec(spec: &FieldSpec) -> FieldSpec {
+    let mut spec: FieldSpec = *spec;
+    spec.kind = EntryKind::Dir;
+    spec.contents = None;
+    spec
+}
+
+
 
-use crate::directory::{Directory, DirectoryExt, Error};
 
 
 /***** LIBRARY *****/
 /// Defines a dynamic directory, like [`HashMap<PathBuf, T>`], except that it only notes nested things for which [`T::exists()`](DirectoryExt::exists()) holds true.
+/// 
+/// Iterating over it happens in a random order. Use [`SortedDynamic<T>`] instead if the order matters. Only the directory itself is scanned; use [`Recursive<T, R>`] to scan its whole subtree.
 #[derive(Clone, Debug)]
-pub struct Dynamic<T>(HashMap<PathBuf, T>);
+pub struct Dynamic<T>(HashMap<PathBuf, T>, PathBuf);
 
 impl<T: DirectoryExt> Directory for Dynamic<T> where Error: From<T::Error> {
     type Error = Error;
 
-    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> {
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
         let base: PathBuf = base.into();
 
         // Scan the directory for directories
         let mut result: HashMap<PathBuf, T> = HashMap::new();
-        let entries: ReadDir = match fs::read_dir(&base) {
-            Ok(entries) => entries,
-            Err(err) => {
-                // If we failed to read the directory because it does not exist, we conclude no files exist either
-                if err.kind() == ErrorKind::NotFound { return Ok(Dynamic(HashMap::new())); }
-                // Otherwise, error hard
-                return Err(Error::DirRead { path: base, err });
-            },
-        };
-        for (i, entry) in entries.enumerate() {
-            // Unwrap the entry
-            let entry: DirEntry = match entry {
-                Ok(entry) => entry,
-                Err(err)  => { return Err(Error::DirEntryRead { path: base, entry: i, err }); },
-            };
-
+        for entry_path in scan(&base, opts)? {
             // Initialize the entry
-            let entry_path: PathBuf = entry.path();
-            let nested: T = T::try_init(&entry_path)?;
+            let nested: T = T::try_init_with(&entry_path, opts)?;
 
             // Filter out nested types which do not exist
-            if !nested.exists() { continue; }
+            if !opts.nested_exists(&nested) { continue; }
 
             // Add the entry to the dynamic set
             result.insert(entry_path, nested);
         }
 
         // Done, return the found entries
-        Ok(Self(result))
+        Ok(Self(result, base))
     }
 }
 impl<T: DirectoryExt> DirectoryExt for Dynamic<T> where Error: From<T::Error> {
@@ -75,6 +102,18 @@ impl<T: DirectoryExt> DirectoryExt for Dynamic<T> where Error: From<T::Error> {
         }
         exists
     }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        // Walk in order of path, to be deterministic
+        let mut entries: Vec<(&PathBuf, &T)> = self.0.iter().collect();
+        entries.sort_by_key(|(path, _)| *path);
+        for (_, nested) in entries {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
 }
 
 impl<T> AsRef<HashMap<PathBuf, T>> for Dynamic<T> {
@@ -129,3 +168,1586 @@ impl<'d, T> IntoIterator for &'d mut Dynamic<T> {
     #[inline]
     fn into_iter(self) -> Self::IntoIter { (&mut self.0).into_iter() }
 }
+
+
+
+
+/// Defines a dynamic directory, like [`Dynamic<T>`], except that its entries are kept sorted by path, like [`BTreeMap<PathBuf, T>`].
+#[derive(Clone, Debug)]
+pub struct SortedDynamic<T>(BTreeMap<PathBuf, T>, PathBuf);
+
+impl<T: DirectoryExt> Directory for SortedDynamic<T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories
+        let mut result: BTreeMap<PathBuf, T> = BTreeMap::new();
+        for entry_path in scan(&base, opts)? {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set
+            result.insert(entry_path, nested);
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base))
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for SortedDynamic<T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for nested in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> AsRef<BTreeMap<PathBuf, T>> for SortedDynamic<T> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<PathBuf, T> { &self.0 }
+}
+impl<T> AsMut<BTreeMap<PathBuf, T>> for SortedDynamic<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<PathBuf, T> { &mut self.0 }
+}
+impl<T> Deref for SortedDynamic<T> {
+    type Target = BTreeMap<PathBuf, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+impl<T> DerefMut for SortedDynamic<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+impl<T> From<SortedDynamic<T>> for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn from(value: SortedDynamic<T>) -> Self { value.0 }
+}
+impl<T: Clone> From<&SortedDynamic<T>> for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn from(value: &SortedDynamic<T>) -> Self { value.0.clone() }
+}
+impl<T: Clone> From<&mut SortedDynamic<T>> for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn from(value: &mut SortedDynamic<T>) -> Self { Self::from(&*value) }
+}
+
+impl<T> IntoIterator for SortedDynamic<T> {
+    type IntoIter = std::collections::btree_map::IntoIter<PathBuf, T>;
+    type Item     = (PathBuf, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+impl<'d, T> IntoIterator for &'d SortedDynamic<T> {
+    type IntoIter = std::collections::btree_map::Iter<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+impl<'d, T> IntoIterator for &'d mut SortedDynamic<T> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter_mut() }
+}
+
+
+
+
+/// Defines a dynamic directory, like [`SortedDynamic<T>`], except that its entries are keyed by their file name instead of their full path.
+/// 
+/// This allows looking up entries by name, e.g., `test_cases["case1"]`. Use [`Named<T>`] instead to have the names as [`String`]s.
+#[derive(Clone, Debug)]
+pub struct ByName<T>(BTreeMap<OsString, T>, PathBuf);
+impl<T> ByName<T> {
+    /// Returns the entry with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// The entry, or [`None`] if there is no entry with that name.
+    #[inline]
+    pub fn get(&self, name: impl AsRef<OsStr>) -> Option<&T> { self.0.get(name.as_ref()) }
+
+    /// Returns the entry with the given name, mutably.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// The entry, or [`None`] if there is no entry with that name.
+    #[inline]
+    pub fn get_mut(&mut self, name: impl AsRef<OsStr>) -> Option<&mut T> { self.0.get_mut(name.as_ref()) }
+
+    /// Returns whether there is an entry with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// True if there is, or false otherwise.
+    #[inline]
+    pub fn contains(&self, name: impl AsRef<OsStr>) -> bool { self.0.contains_key(name.as_ref()) }
+}
+
+impl<T: DirectoryExt> Directory for ByName<T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories
+        let mut result: BTreeMap<OsString, T> = BTreeMap::new();
+        for entry_path in scan(&base, opts)? {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set
+            let name: OsString = entry_path.file_name().map(OsStr::to_os_string).unwrap_or_default();
+            result.insert(name, nested);
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base))
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for ByName<T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for nested in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> AsRef<BTreeMap<OsString, T>> for ByName<T> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<OsString, T> { &self.0 }
+}
+impl<T> AsMut<BTreeMap<OsString, T>> for ByName<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<OsString, T> { &mut self.0 }
+}
+impl<T> Deref for ByName<T> {
+    type Target = BTreeMap<OsString, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+impl<T> DerefMut for ByName<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+impl<T> From<ByName<T>> for BTreeMap<OsString, T> {
+    #[inline]
+    fn from(value: ByName<T>) -> Self { value.0 }
+}
+impl<T, N: ?Sized + AsRef<OsStr>> Index<&N> for ByName<T> {
+    type Output = T;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, name: &N) -> &Self::Output {
+        let name: &OsStr = name.as_ref();
+        self.0.get(name).unwrap_or_else(|| panic!("No entry with name '{}'", name.to_string_lossy()))
+    }
+}
+
+impl<T> IntoIterator for ByName<T> {
+    type IntoIter = std::collections::btree_map::IntoIter<OsString, T>;
+    type Item     = (OsString, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+impl<'d, T> IntoIterator for &'d ByName<T> {
+    type IntoIter = std::collections::btree_map::Iter<'d, OsString, T>;
+    type Item     = (&'d OsString, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+impl<'d, T> IntoIterator for &'d mut ByName<T> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, OsString, T>;
+    type Item     = (&'d OsString, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter_mut() }
+}
+
+
+
+
+/// Defines a dynamic directory, like [`ByName<T>`], except that its entries are keyed by their file name as a [`String`].
+/// 
+/// Entries of which the name is not valid UTF-8 either cause initialization to fail or are skipped, depending on the [`NamePolicy`] in the [`InitOptions`] (see `#[directories(names = ...)]`).
+#[derive(Clone, Debug)]
+pub struct Named<T>(BTreeMap<String, T>, PathBuf);
+impl<T> Named<T> {
+    /// Returns the entry with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// The entry, or [`None`] if there is no entry with that name.
+    #[inline]
+    pub fn get(&self, name: impl AsRef<str>) -> Option<&T> { self.0.get(name.as_ref()) }
+
+    /// Returns the entry with the given name, mutably.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// The entry, or [`None`] if there is no entry with that name.
+    #[inline]
+    pub fn get_mut(&mut self, name: impl AsRef<str>) -> Option<&mut T> { self.0.get_mut(name.as_ref()) }
+
+    /// Returns whether there is an entry with the given name.
+    /// 
+    /// # Arguments
+    /// - `name`: The file name of the entry (e.g., `case1`).
+    /// 
+    /// # Returns
+    /// True if there is, or false otherwise.
+    #[inline]
+    pub fn contains(&self, name: impl AsRef<str>) -> bool { self.0.contains_key(name.as_ref()) }
+}
+
+impl<T: DirectoryExt> Directory for Named<T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories
+        let mut result: BTreeMap<String, T> = BTreeMap::new();
+        for entry_path in scan(&base, opts)? {
+            // Apply the name policy
+            let name: String = match entry_path.file_name().map(OsStr::to_str) {
+                Some(Some(name)) => name.into(),
+                _ => match opts.names {
+                    NamePolicy::Error => { return Err(Error::NonUtf8Name { path: entry_path }); },
+                    NamePolicy::Skip  => { continue; },
+                },
+            };
+
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set
+            result.insert(name, nested);
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base))
+    }
+}
+impl<T: DirectoryExt> DirectoryExt for Named<T> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for nested in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> AsRef<BTreeMap<String, T>> for Named<T> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<String, T> { &self.0 }
+}
+impl<T> AsMut<BTreeMap<String, T>> for Named<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<String, T> { &mut self.0 }
+}
+impl<T> Deref for Named<T> {
+    type Target = BTreeMap<String, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+impl<T> DerefMut for Named<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+impl<T> From<Named<T>> for BTreeMap<String, T> {
+    #[inline]
+    fn from(value: Named<T>) -> Self { value.0 }
+}
+impl<T, N: ?Sized + AsRef<str>> Index<&N> for Named<T> {
+    type Output = T;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, name: &N) -> &Self::Output {
+        let name: &str = name.as_ref();
+        self.0.get(name).unwrap_or_else(|| panic!("No entry with name '{name}'"))
+    }
+}
+
+impl<T> IntoIterator for Named<T> {
+    type IntoIter = std::collections::btree_map::IntoIter<String, T>;
+    type Item     = (String, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+impl<'d, T> IntoIterator for &'d Named<T> {
+    type IntoIter = std::collections::btree_map::Iter<'d, String, T>;
+    type Item     = (&'d String, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+impl<'d, T> IntoIterator for &'d mut Named<T> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, String, T>;
+    type Item     = (&'d String, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter_mut() }
+}
+
+
+
+
+/// Defines a dynamic directory, like [`Named<T>`], except that the name of every entry is parsed as a key of type `K` with [`FromStr`].
+/// 
+/// Entries are sorted by key, so for example versioned release directories (`1.2.0/`, `1.3.0/`, ...) keyed by a version type make finding the [`Keyed::latest()`] trivial. Entries of which the name cannot be parsed, or parses to a key that another entry already has (e.g., `01` and `1`), are skipped by default, or either cause initialization to fail or are collected in [`Keyed::rejects()`], depending on the [`KeyPolicy`] in the [`InitOptions`] (see `#[directories(keys = ...)]`).
+#[derive(Clone, Debug)]
+pub struct Keyed<K, T> {
+    /// The path of the directory itself.
+    path    : PathBuf,
+    /// The entries, by key.
+    entries : BTreeMap<K, T>,
+    /// The paths of entries of which the name could not be parsed, if collected.
+    rejects : Vec<PathBuf>,
+}
+impl<K: Ord, T> Keyed<K, T> {
+    /// Returns the entry with the largest key, e.g., the latest version.
+    /// 
+    /// # Returns
+    /// The key and the entry, or [`None`] if there are no entries.
+    #[inline]
+    pub fn latest(&self) -> Option<(&K, &T)> { self.entries.last_key_value() }
+
+    /// Returns the entry with the smallest key, e.g., the oldest version.
+    /// 
+    /// # Returns
+    /// The key and the entry, or [`None`] if there are no entries.
+    #[inline]
+    pub fn oldest(&self) -> Option<(&K, &T)> { self.entries.first_key_value() }
+
+    /// Returns the paths of entries of which the name could not be parsed as a key, or of which the key was already taken by another entry.
+    /// 
+    /// This is only populated if the [`KeyPolicy`] is [`KeyPolicy::Collect`]. The entries are sorted by path.
+    #[inline]
+    pub fn rejects(&self) -> &[PathBuf] { &self.rejects }
+}
+
+impl<K: FromStr + Ord, T: DirectoryExt> Directory for Keyed<K, T> where K::Err: error::Error + Send + Sync + 'static, Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories, in a fixed order so it's deterministic which of any duplicate keys is kept
+        let mut paths: Vec<PathBuf> = scan(&base, opts)?;
+        paths.sort();
+        let mut entries: BTreeMap<K, (PathBuf, T)> = BTreeMap::new();
+        let mut rejects: Vec<PathBuf> = vec![];
+        for entry_path in paths {
+            // Apply the name policy
+            let name: &str = match entry_path.file_name().map(OsStr::to_str) {
+                Some(Some(name)) => name,
+                _ => match opts.names {
+                    NamePolicy::Error => { return Err(Error::NonUtf8Name { path: entry_path }); },
+                    NamePolicy::Skip  => { continue; },
+                },
+            };
+
+            // Apply the key policy
+            let key: K = match name.parse() {
+                Ok(key) => key,
+                Err(err) => match opts.keys {
+                    KeyPolicy::Error   => { return Err(Error::Key { path: entry_path, err: Box::new(err) }); },
+                    KeyPolicy::Skip    => {
+                        #[cfg(feature = "log")]
+                        log::warn!("Skipping '{}' because its name is not a valid key: {err}", entry_path.display());
+                        #[cfg(not(feature = "log"))]
+                        let _ = err;
+                        continue;
+                    },
+                    KeyPolicy::Collect => { rejects.push(entry_path); continue; },
+                },
+            };
+
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set, applying the key policy to duplicates as well
+            match entries.entry(key) {
+                btree_map::Entry::Vacant(entry) => { entry.insert((entry_path, nested)); },
+                btree_map::Entry::Occupied(entry) => match opts.keys {
+                    KeyPolicy::Error   => { return Err(Error::DuplicateKey { path: entry_path, other: entry.get().0.clone() }); },
+                    KeyPolicy::Skip    => {
+                        #[cfg(feature = "log")]
+                        log::warn!("Skipping '{}' because it has the same key as '{}'", entry_path.display(), entry.get().0.display());
+                        continue;
+                    },
+                    KeyPolicy::Collect => { rejects.push(entry_path); continue; },
+                },
+            }
+        }
+
+        // Done, return the found entries
+        let entries: BTreeMap<K, T> = entries.into_iter().map(|(key, (_, nested))| (key, nested)).collect();
+        Ok(Self { path: base, entries, rejects })
+    }
+}
+impl<K: FromStr + Ord, T: DirectoryExt> DirectoryExt for Keyed<K, T> where K::Err: error::Error + Send + Sync + 'static, Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.entries.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.path, &dir_spec(spec))?;
+        for nested in self.entries.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, T> AsRef<BTreeMap<K, T>> for Keyed<K, T> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<K, T> { &self.entries }
+}
+impl<K, T> AsMut<BTreeMap<K, T>> for Keyed<K, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<K, T> { &mut self.entries }
+}
+impl<K, T> Deref for Keyed<K, T> {
+    type Target = BTreeMap<K, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.entries }
+}
+impl<K, T> DerefMut for Keyed<K, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.entries }
+}
+impl<K, T> From<Keyed<K, T>> for BTreeMap<K, T> {
+    #[inline]
+    fn from(value: Keyed<K, T>) -> Self { value.entries }
+}
+
+impl<K, T> IntoIterator for Keyed<K, T> {
+    type IntoIter = std::collections::btree_map::IntoIter<K, T>;
+    type Item     = (K, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.entries.into_iter() }
+}
+impl<'d, K, T> IntoIterator for &'d Keyed<K, T> {
+    type IntoIter = std::collections::btree_map::Iter<'d, K, T>;
+    type Item     = (&'d K, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.entries.iter() }
+}
+impl<'d, K, T> IntoIterator for &'d mut Keyed<K, T> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, K, T>;
+    type Item     = (&'d K, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.entries.iter_mut() }
+}
+
+
+
+
+/// Defines the glob patterns of a [`Glob<T, P>`].
+/// 
+/// For example:
+/// ```rust,ignore
+/// struct Sources;
+/// impl GlobPatterns for Sources {
+///     const PATTERNS: &'static [&'static str] = &["*.rs", "!*_test.rs"];
+/// }
+/// 
+/// #[derive(Directory)]
+/// struct Crate {
+///     #[dir(path = "src")]
+///     sources : Glob<PathBuf, Sources>,
+/// }
+/// ```
+pub trait GlobPatterns {
+    /// The patterns that the names of entries must match. Patterns starting with `!` exclude entries instead (see [`Patterns::new()`]).
+    const PATTERNS: &'static [&'static str];
+}
+
+/// Defines a dynamic directory, like [`SortedDynamic<T>`], except that it only notes entries of which the name matches the patterns given by `P`.
+/// 
+/// This is the type-level alternative to putting `#[dir(glob = ...)]` on a dynamic field.
+pub struct Glob<T, P>(BTreeMap<PathBuf, T>, PathBuf, PhantomData<P>);
+
+impl<T: DirectoryExt, P: GlobPatterns> Directory for Glob<T, P> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for entries matching the patterns
+        let mut result: BTreeMap<PathBuf, T> = BTreeMap::new();
+        for entry_path in scan(&base, &opts.with_glob(&base, Patterns::new(P::PATTERNS)))? {
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set
+            result.insert(entry_path, nested);
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base, PhantomData))
+    }
+}
+impl<T: DirectoryExt, P: GlobPatterns> DirectoryExt for Glob<T, P> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for nested in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Clone, P> Clone for Glob<T, P> {
+    #[inline]
+    fn clone(&self) -> Self { Self(self.0.clone(), self.1.clone(), PhantomData) }
+}
+impl<T: Debug, P> Debug for Glob<T, P> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { f.debug_tuple("Glob").field(&self.0).finish() }
+}
+
+impl<T, P> AsRef<BTreeMap<PathBuf, T>> for Glob<T, P> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<PathBuf, T> { &self.0 }
+}
+impl<T, P> AsMut<BTreeMap<PathBuf, T>> for Glob<T, P> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<PathBuf, T> { &mut self.0 }
+}
+impl<T, P> Deref for Glob<T, P> {
+    type Target = BTreeMap<PathBuf, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+impl<T, P> DerefMut for Glob<T, P> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+impl<T, P> From<Glob<T, P>> for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn from(value: Glob<T, P>) -> Self { value.0 }
+}
+
+impl<T, P> IntoIterator for Glob<T, P> {
+    type IntoIter = std::collections::btree_map::IntoIter<PathBuf, T>;
+    type Item     = (PathBuf, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+impl<'d, T, P> IntoIterator for &'d Glob<T, P> {
+    type IntoIter = std::collections::btree_map::Iter<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+impl<'d, T, P> IntoIterator for &'d mut Glob<T, P> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter_mut() }
+}
+
+
+
+
+/// Defines a dynamic directory, like [`SortedDynamic<T>`], except that it also notes the groups captured by the regular expression given with `#[dir(regex = ...)]` from the name of every entry.
+/// 
+/// The captures are converted to a key of type `K` with [`FromCaptures`], which are the [`Captures`] themselves by default. If no regular expression is given, every entry is collected and only captures its whole name.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct Matched<T, K = Captures>(BTreeMap<PathBuf, (K, T)>, PathBuf);
+#[cfg(feature = "regex")]
+impl<T, K> Matched<T, K> {
+    /// Returns an iterator over the keys and entries, sorted by path.
+    #[inline]
+    pub fn entries(&self) -> impl Iterator<Item = (&K, &T)> { self.0.values().map(|(k, e)| (k, e)) }
+}
+
+#[cfg(feature = "regex")]
+impl<T: DirectoryExt, K: FromCaptures> Directory for Matched<T, K> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // Scan the directory for directories (which only returns those matching the regex, if any)
+        let mut result: BTreeMap<PathBuf, (K, T)> = BTreeMap::new();
+        for entry_path in scan(&base, opts)? {
+            // Capture the groups in its name
+            let name: String = entry_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let captures: Captures = match &opts.regex {
+                Some((dir, regex)) if *dir == base => match Captures::new(regex, &name) {
+                    Some(captures) => captures,
+                    None           => { continue; },
+                },
+                _ => Captures::whole(name),
+            };
+            let key: K = K::from_captures(&captures).map_err(|err| Error::Capture { path: entry_path.clone(), err })?;
+
+            // Initialize the entry
+            let nested: T = T::try_init_with(&entry_path, opts)?;
+
+            // Filter out nested types which do not exist
+            if !opts.nested_exists(&nested) { continue; }
+
+            // Add the entry to the dynamic set
+            result.insert(entry_path, (key, nested));
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base))
+    }
+}
+#[cfg(feature = "regex")]
+impl<T: DirectoryExt, K: FromCaptures> DirectoryExt for Matched<T, K> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for (_, nested) in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for (_, nested) in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl<T, K> AsRef<BTreeMap<PathBuf, (K, T)>> for Matched<T, K> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<PathBuf, (K, T)> { &self.0 }
+}
+#[cfg(feature = "regex")]
+impl<T, K> AsMut<BTreeMap<PathBuf, (K, T)>> for Matched<T, K> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<PathBuf, (K, T)> { &mut self.0 }
+}
+#[cfg(feature = "regex")]
+impl<T, K> Deref for Matched<T, K> {
+    type Target = BTreeMap<PathBuf, (K, T)>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+#[cfg(feature = "regex")]
+impl<T, K> DerefMut for Matched<T, K> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+#[cfg(feature = "regex")]
+impl<T, K> From<Matched<T, K>> for BTreeMap<PathBuf, (K, T)> {
+    #[inline]
+    fn from(value: Matched<T, K>) -> Self { value.0 }
+}
+
+#[cfg(feature = "regex")]
+impl<T, K> IntoIterator for Matched<T, K> {
+    type IntoIter = std::collections::btree_map::IntoIter<PathBuf, (K, T)>;
+    type Item     = (PathBuf, (K, T));
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+#[cfg(feature = "regex")]
+impl<'d, T, K> IntoIterator for &'d Matched<T, K> {
+    type IntoIter = std::collections::btree_map::Iter<'d, PathBuf, (K, T)>;
+    type Item     = (&'d PathBuf, &'d (K, T));
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+
+
+
+
+/// Defines how a [`Recursive<T, R>`] walks its subtree.
+/// 
+/// For example:
+/// ```rust,ignore
+/// struct Categories;
+/// impl Recursion for Categories {
+///     const MAX_DEPTH: Option<usize> = Some(4);
+/// 
+///     fn prune(path: &Path, _depth: usize) -> bool { path.file_name().is_some_and(|n| n == "target") }
+/// }
+/// 
+/// #[derive(Directory)]
+/// struct Tests {
+///     #[dir(path = "cases")]
+///     cases : Recursive<TestCase, Categories>,
+/// }
+/// ```
+pub trait Recursion {
+    /// The maximum depth of entries to collect, where entries directly in the directory have depth 1. [`None`] means unbounded.
+    const MAX_DEPTH: Option<usize> = None;
+
+    /// Returns whether an entry should be collected.
+    /// 
+    /// Entries that are not collected are still descended into if they are directories. Note that entries are only collected if the nested type exists, too.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the entry.
+    /// - `depth`: The depth of the entry, where entries directly in the directory have depth 1.
+    /// 
+    /// # Returns
+    /// True to collect the entry, or false otherwise. Defaults to true.
+    #[inline]
+    fn matches(_path: &Path, _depth: usize) -> bool { true }
+
+    /// Returns whether a directory should be pruned, i.e., not descended into.
+    /// 
+    /// Pruned directories may still be collected themselves.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the directory.
+    /// - `depth`: The depth of the directory, where entries directly in the directory have depth 1.
+    /// 
+    /// # Returns
+    /// True to prune the directory, or false otherwise. Defaults to false.
+    #[inline]
+    fn prune(_path: &Path, _depth: usize) -> bool { false }
+}
+
+/// Defines the default [`Recursion`] of a [`Recursive<T, R>`], which walks the whole subtree.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unbounded;
+impl Recursion for Unbounded {}
+
+/// Defines a dynamic directory, like [`SortedDynamic<T>`], except that it walks the whole subtree instead of only the directory itself.
+/// 
+/// Every entry in the subtree is initialized as `T` and noted if it exists, keyed by its full path. How the subtree is walked (e.g., up to which depth, or which directories to skip) is determined by `R` (see [`Recursion`]). Every directory is only walked once, such that symlinks pointing to one of their ancestors don't cause infinite loops. Any glob patterns or regular expression given with `#[dir(glob = ...)]` or `#[dir(regex = ...)]` filter the names of the entries collected at every depth, but not which directories are walked.
+pub struct Recursive<T, R = Unbounded>(BTreeMap<PathBuf, T>, PathBuf, PhantomData<R>);
+
+impl<T: DirectoryExt, R: Recursion> Directory for Recursive<T, R> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let base: PathBuf = base.into();
+
+        // The filters on the names are applied to all entries ourselves, so don't let them prevent us from walking the subtree
+        let mut walk_opts: InitOptions = opts.clone();
+        walk_opts.glob = None;
+        #[cfg(feature = "regex")]
+        {
+            walk_opts.regex = None;
+        }
+
+        // Walk the subtree, remembering which directories we've seen
+        let mut result: BTreeMap<PathBuf, T> = BTreeMap::new();
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        if let Some(dir) = opts.canonical_dir(&base)? { visited.insert(dir); }
+        let mut todo: Vec<(PathBuf, usize)> = vec![(base.clone(), 1)];
+        while let Some((dir, depth)) = todo.pop() {
+            if R::MAX_DEPTH.is_some_and(|max| depth > max) { continue; }
+            for entry_path in scan(&dir, &walk_opts)? {
+                // Initialize the entry if it should be collected
+                if opts.name_matches(&base, entry_path.file_name().unwrap_or_default()) && R::matches(&entry_path, depth) {
+                    let nested: T = T::try_init_with(&entry_path, opts)?;
+
+                    // Filter out nested types which do not exist
+                    if opts.nested_exists(&nested) { result.insert(entry_path.clone(), nested); }
+                }
+
+                // Descend into directories we haven't seen yet
+                if R::prune(&entry_path, depth) { continue; }
+                if let Some(dir) = opts.canonical_dir(&entry_path)? {
+                    if visited.insert(dir) { todo.push((entry_path, depth + 1)); }
+                }
+            }
+        }
+
+        // Done, return the found entries
+        Ok(Self(result, base, PhantomData))
+    }
+}
+impl<T: DirectoryExt, R: Recursion> DirectoryExt for Recursive<T, R> where Error: From<T::Error> {
+    fn exists(&self) -> bool {
+        // Iterate to only check those we found
+        let mut exists: bool = true;
+        for nested in self.0.values() {
+            exists &= nested.exists();
+        }
+        exists
+    }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        // Visit the directory itself first (as declared), so that it is created and validated too; the entries are declared by their own type
+        visit(&self.1, &dir_spec(spec))?;
+        for nested in self.0.values() {
+            nested.walk(&FieldSpec::default(), visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Clone, R> Clone for Recursive<T, R> {
+    #[inline]
+    fn clone(&self) -> Self { Self(self.0.clone(), self.1.clone(), PhantomData) }
+}
+impl<T: Debug, R> Debug for Recursive<T, R> {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { f.debug_tuple("Recursive").field(&self.0).finish() }
+}
+
+impl<T, R> AsRef<BTreeMap<PathBuf, T>> for Recursive<T, R> {
+    #[inline]
+    fn as_ref(&self) -> &BTreeMap<PathBuf, T> { &self.0 }
+}
+impl<T, R> AsMut<BTreeMap<PathBuf, T>> for Recursive<T, R> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut BTreeMap<PathBuf, T> { &mut self.0 }
+}
+impl<T, R> Deref for Recursive<T, R> {
+    type Target = BTreeMap<PathBuf, T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+impl<T, R> DerefMut for Recursive<T, R> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+impl<T, R> From<Recursive<T, R>> for BTreeMap<PathBuf, T> {
+    #[inline]
+    fn from(value: Recursive<T, R>) -> Self { value.0 }
+}
+
+impl<T, R> IntoIterator for Recursive<T, R> {
+    type IntoIter = std::collections::btree_map::IntoIter<PathBuf, T>;
+    type Item     = (PathBuf, T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.into_iter() }
+}
+impl<'d, T, R> IntoIterator for &'d Recursive<T, R> {
+    type IntoIter = std::collections::btree_map::Iter<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter() }
+}
+impl<'d, T, R> IntoIterator for &'d mut Recursive<T, R> {
+    type IntoIter = std::collections::btree_map::IterMut<'d, PathBuf, T>;
+    type Item     = (&'d PathBuf, &'d mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter { self.0.iter_mut() }
+}
+
+
+
+
+/// Defines a symlink, of which the target is read when it is initialized.
+/// 
+/// When validated, it asserts that the path is indeed a symlink (unless declared otherwise with `#[file(symlink = ...)]` or `#[dir(symlink = ...)]`).
+#[derive(Clone, Debug)]
+pub struct Symlink {
+    /// The path of the symlink itself.
+    path   : PathBuf,
+    /// The target of the symlink, as it is written in the link.
+    target : Option<PathBuf>,
+}
+impl Symlink {
+    /// Returns the path of the symlink itself.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the target of the symlink, as it is written in the link (i.e., it may be relative to the link's directory).
+    /// 
+    /// # Returns
+    /// The target, or [`None`] if the path was not a symlink (or did not exist) when this Symlink was initialized.
+    #[inline]
+    pub fn target(&self) -> Option<&Path> { self.target.as_deref() }
+
+    /// Resolves the symlink to the canonical path it points to.
+    /// 
+    /// # Returns
+    /// The canonical target, or [`None`] if the path is not (or no longer) a symlink or it points to something that does not exist.
+    #[inline]
+    pub fn resolve(&self) -> Option<PathBuf> {
+        if !self.exists() { return None; }
+        fs::canonicalize(&self.path).ok()
+    }
+}
+
+impl Directory for Symlink {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let path: PathBuf = base.into();
+
+        // Read the link through the capability, if any
+        #[cfg(feature = "cap-std")]
+        let res: std::io::Result<PathBuf> = match &opts.cap {
+            Some(cap) => cap.read_link(crate::cap::rel(&path)),
+            None      => fs::read_link(&path),
+        };
+        #[cfg(not(feature = "cap-std"))]
+        let res: std::io::Result<PathBuf> = { let _ = opts; fs::read_link(&path) };
+        let target: Option<PathBuf> = match res {
+            Ok(target) => Some(target),
+            // It doesn't exist or isn't a symlink
+            Err(err) if err.kind() == ErrorKind::NotFound || err.kind() == ErrorKind::InvalidInput => None,
+            Err(err) => { return Err(Error::LinkRead { path, err }); },
+        };
+        Ok(Self { path, target })
+    }
+}
+impl DirectoryExt for Symlink {
+    /// Checks whether the path is (still) a symlink on disk, regardless of whether it was one when this Symlink was initialized.
+    #[inline]
+    fn exists(&self) -> bool { fs::symlink_metadata(&self.path).map(|md| md.file_type().is_symlink()).unwrap_or(false) }
+
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        let mut spec: FieldSpec = *spec;
+        if spec.symlink.is_none() { spec.symlink = Some(LinkTarget::Anywhere); }
+        visit(&self.path, &spec)
+    }
+}
+
+impl AsRef<Path> for Symlink {
+    #[inline]
+    fn as_ref(&self) -> &Path { &self.path }
+}
+
+
+
+/// Defines a directory that is kept open while the nested directory `T` is used, to avoid races between initializing and using it.
+/// 
+/// When initialized, the directory is opened as a [`Dir`] handle first, after which `T` is initialized with that handle as its capability. As such, everything in `T` is resolved relative to the same directory that was opened (openat-style), even if it is moved or replaced on the original path in the meantime.
+/// 
+/// If initialized with [`Directory::try_init_cap()`], the directory itself is opened through that capability; otherwise, it is opened on the ambient filesystem.
+/// 
+/// This is implemented with [`cap_std`], and thus requires the `cap-std` feature (which is enabled by default).
+/// 
+/// Note that the paths in `T` are relative to [`Opened::handle()`], and should be used with it (e.g., `opened.handle().unwrap().open(&opened.file)`). [`DirectoryExt::walk()`] reports them relative to [`Opened::path()`] instead, such that rendering or ensuring the layout works as usual. If the directory did not exist when this Opened was initialized, `T` is initialized with the original path instead.
+#[cfg(feature = "cap-std")]
+#[derive(Debug)]
+pub struct Opened<T> {
+    /// The original path of the directory (relative to the capability, if any).
+    path   : PathBuf,
+    /// The opened directory, if it existed.
+    handle : Option<Dir>,
+    /// The nested directory, initialized relative to the handle if there is any.
+    inner  : T,
+}
+#[cfg(feature = "cap-std")]
+impl<T> Opened<T> {
+    /// Returns the original path of the directory, which is relative to the capability if it was initialized with one.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the opened directory, relative to which the paths in the nested directory are.
+    /// 
+    /// # Returns
+    /// The [`Dir`] handle, or [`None`] if it did not exist (or was not a directory) when this Opened was initialized.
+    #[inline]
+    pub fn handle(&self) -> Option<&Dir> { self.handle.as_ref() }
+
+    /// Returns the given path in the nested directory as a path relative to the original one.
+    /// 
+    /// # Arguments
+    /// - `path`: The path in the nested directory.
+    /// 
+    /// # Returns
+    /// The path joined to [`Opened::path()`] if the directory was opened, or else the path as-is.
+    pub fn reroot(&self, path: &Path) -> PathBuf {
+        if self.handle.is_none() { return path.into(); }
+        if path.as_os_str().is_empty() { self.path.clone() } else { self.path.join(path) }
+    }
+}
+
+#[cfg(feature = "cap-std")]
+impl<T: Directory> Directory for Opened<T> where Error: From<T::Error> {
+    type Error = Error;
+
+    #[inline]
+    fn try_init(base: impl Into<PathBuf>) -> Result<Self, Self::Error> { Self::try_init_with(base, &InitOptions::default()) }
+
+    fn try_init_with(base: impl Into<PathBuf>, opts: &InitOptions) -> Result<Self, Self::Error> {
+        let path: PathBuf = base.into();
+
+        // Open the directory first
+        let res: std::io::Result<Dir> = match &opts.cap {
+            Some(cap) => cap.open_dir(crate::cap::rel(&path)),
+            None      => Dir::open_ambient_dir(&path, ambient_authority()),
+        };
+        let handle: Dir = match res {
+            Ok(handle) => handle,
+            // It doesn't exist or isn't a directory, so initialize it as usual
+            Err(err) if err.kind() == ErrorKind::NotFound || err.kind() == ErrorKind::NotADirectory => {
+                let inner: T = T::try_init_with(path.clone(), opts)?;
+                return Ok(Self { path, handle: None, inner });
+            },
+            Err(err) => { return Err(Error::Open { path, err }); },
+        };
+
+        // Then initialize the nested directory with it as capability, which also confines it
+        let mut nested: InitOptions = opts.clone();
+        nested.cap = Some(Arc::new(handle.try_clone().map_err(|err| Error::Open { path: path.clone(), err })?));
+        nested.confine = None;
+        // Any patterns given for this directory now apply to the root of the capability
+        if let Some((dir, _)) = &mut nested.glob {
+            if let Ok(suffix) = dir.strip_prefix(&path) { *dir = suffix.into(); }
+        }
+        #[cfg(feature = "regex")]
+        if let Some((dir, _)) = &mut nested.regex {
+            if let Ok(suffix) = dir.strip_prefix(&path) { *dir = suffix.into(); }
+        }
+        let inner: T = T::try_init_with(PathBuf::new(), &nested)?;
+        Ok(Self { path, handle: Some(handle), inner })
+    }
+}
+#[cfg(feature = "cap-std")]
+impl<T: DirectoryExt> DirectoryExt for Opened<T> where Error: From<T::Error> {
+    /// Checks whether the nested directory exists relative to the opened directory.
+    fn exists(&self) -> bool {
+        match &self.handle {
+            Some(handle) => self.inner.exists_cap(handle),
+            None         => self.inner.exists(),
+        }
+    }
+
+    /// Validates the nested directory relative to the opened directory, reporting any problems relative to the original path.
+    fn validate(&self) -> Vec<Problem> {
+        match &self.handle {
+            Some(handle) => {
+                let mut problems: Vec<Problem> = self.inner.validate_cap(handle);
+                for problem in &mut problems {
+                    let path: PathBuf = self.reroot(problem.path());
+                    *problem.path_mut() = path;
+                }
+                problems
+            },
+            None => self.inner.validate(),
+        }
+    }
+
+    #[inline]
+    fn lockfile(&self) -> Option<PathBuf> { self.inner.lockfile().map(|path| self.reroot(&path)) }
+
+    #[inline]
+    fn walk<E>(&self, spec: &FieldSpec, visit: &mut dyn FnMut(&Path, &FieldSpec) -> Result<(), E>) -> Result<(), E> {
+        self.inner.walk(spec, &mut |path: &Path, spec: &FieldSpec| visit(&self.reroot(path), spec))
+    }
+}
+#[cfg(feature = "cap-std")]
+impl<T> Deref for Opened<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.inner }
+}
+#[cfg(feature = "cap-std")]
+impl<T> DerefMut for Opened<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.inner }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::directory::Problem;
+
+    /// Creates a fresh, empty directory for a test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("directories-test-std-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Walks the given directory with the given spec, returning every path visited together with its declared kind and mode.
+    fn visited<D: DirectoryExt>(dir: &D, spec: &FieldSpec) -> Vec<(PathBuf, EntryKind, Option<u32>)> {
+        let mut paths: Vec<(PathBuf, EntryKind, Option<u32>)> = vec![];
+        let res: Result<(), std::convert::Infallible> = dir.walk(spec, &mut |path: &Path, spec: &FieldSpec| {
+            paths.push((path.into(), spec.kind, spec.mode));
+            Ok(())
+        });
+        if let Err(err) = res { match err {} }
+        paths
+    }
+
+
+    #[test]
+    fn test_walk_spec() {
+        let root: PathBuf = scratch("walk");
+        fs::create_dir(root.join("logs")).unwrap();
+        for name in ["1", "2"] { fs::write(root.join("logs").join(name), "").unwrap(); }
+        let logs: PathBuf = root.join("logs");
+
+        // The spec of the field applies to the directory itself, while the entries are declared by their own type
+        let mut spec: FieldSpec = FieldSpec::new(EntryKind::Dir);
+        spec.mode = Some(0o700);
+        let expected: Vec<(PathBuf, EntryKind, Option<u32>)> = vec![
+            (logs.clone(), EntryKind::Dir, Some(0o700)),
+            (logs.join("1"), EntryKind::Any, None),
+            (logs.join("2"), EntryKind::Any, None),
+        ];
+        struct Digits;
+        impl GlobPatterns for Digits {
+            const PATTERNS: &'static [&'static str] = &["[0-9]"];
+        }
+        assert_eq!(visited(&Dynamic::<PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&SortedDynamic::<PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&ByName::<PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&Named::<PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&Keyed::<u32, PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&Glob::<PathBuf, Digits>::try_init(&logs).unwrap(), &spec), expected);
+        #[cfg(feature = "regex")]
+        assert_eq!(visited(&Matched::<PathBuf, Captures>::try_init(&logs).unwrap(), &spec), expected);
+        assert_eq!(visited(&Recursive::<PathBuf>::try_init(&logs).unwrap(), &spec), expected);
+
+        // Even if the field doesn't declare anything, the directory itself is still a directory
+        assert_eq!(visited(&Dynamic::<PathBuf>::try_init(&logs).unwrap(), &FieldSpec::default())[0], (logs, EntryKind::Dir, None));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_exists_missing() {
+        let root: PathBuf = scratch("exists");
+        let missing: PathBuf = root.join("missing");
+
+        // A dynamic directory that doesn't exist is simply empty...
+        assert!(Dynamic::<PathBuf>::try_init(&missing).unwrap().exists());
+        assert!(SortedDynamic::<PathBuf>::try_init(&missing).unwrap().exists());
+        assert!(ByName::<PathBuf>::try_init(&missing).unwrap().exists());
+        assert!(Named::<PathBuf>::try_init(&missing).unwrap().exists());
+        assert!(Keyed::<u32, PathBuf>::try_init(&missing).unwrap().exists());
+        #[cfg(feature = "regex")]
+        assert!(Matched::<PathBuf, Captures>::try_init(&missing).unwrap().exists());
+        assert!(Recursive::<PathBuf>::try_init(&missing).unwrap().exists());
+
+        // Validating still reports that it's missing, though
+        assert!(matches!(Dynamic::<PathBuf>::try_init(&missing).unwrap().validate().as_slice(), [Problem::Missing { path, kind: EntryKind::Dir }] if *path == missing));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_keyed_policies() {
+        let root: PathBuf = scratch("keyed");
+        for name in ["01", "1", "2"] { fs::create_dir(root.join(name)).unwrap(); }
+        fs::write(root.join("README.md"), "").unwrap();
+
+        // By default, anything that isn't (uniquely) a key is skipped
+        let keyed: Keyed<u32, PathBuf> = Keyed::try_init(&root).unwrap();
+        assert_eq!(keyed.keys().copied().collect::<Vec<u32>>(), [1, 2]);
+        assert_eq!(keyed[&1], root.join("01"));
+        assert_eq!(keyed.latest(), Some((&2, &root.join("2"))));
+        assert!(keyed.rejects().is_empty());
+
+        // Or collected...
+        let opts: InitOptions = InitOptions { keys: KeyPolicy::Collect, ..Default::default() };
+        let keyed: Keyed<u32, PathBuf> = Keyed::try_init_with(&root, &opts).unwrap();
+        assert_eq!(keyed.len(), 2);
+        assert_eq!(keyed.rejects(), [root.join("1"), root.join("README.md")]);
+
+        // ...or an error, where the duplicate is found first
+        let opts: InitOptions = InitOptions { keys: KeyPolicy::Error, ..Default::default() };
+        assert!(matches!(Keyed::<u32, PathBuf>::try_init_with(&root, &opts), Err(Error::DuplicateKey { path, other }) if path == root.join("1") && other == root.join("01")));
+        fs::remove_dir(root.join("1")).unwrap();
+        assert!(matches!(Keyed::<u32, PathBuf>::try_init_with(&root, &opts), Err(Error::Key { path, .. }) if path == root.join("README.md")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_exists_missing_nested() {
+        #[derive(crate::Directory)]
+        struct Case {
+            #[dir]
+            results: Dynamic<PathBuf>,
+        }
+
+        // Entries aren't dropped because their own dynamic directory doesn't exist yet
+        let root: PathBuf = scratch("exists-nested");
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b/results")).unwrap();
+        let cases: Dynamic<Case> = Dynamic::try_init(&root).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert!(cases[&root.join("a")].results.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "derive", unix))]
+    #[test]
+    fn test_ensure_mode() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        #[derive(crate::Directory)]
+        struct Secrets {
+            #[dir(mode = 0o700)]
+            keys: Dynamic<PathBuf>,
+        }
+
+        // Ensuring creates the dynamic directory with the declared permissions...
+        let root: PathBuf = scratch("ensure-mode");
+        let secrets: Secrets = Secrets::try_init(&root).unwrap();
+        secrets.ensure().unwrap();
+        assert_eq!(fs::metadata(root.join("keys")).unwrap().permissions().mode() & 0o7777, 0o700);
+
+        // ...which collecting its entries again doesn't change
+        fs::write(root.join("keys/id"), "").unwrap();
+        let secrets: Secrets = Secrets::try_init(&root).unwrap();
+        assert_eq!(secrets.keys.len(), 1);
+        secrets.ensure().unwrap();
+        assert_eq!(fs::metadata(root.join("keys")).unwrap().permissions().mode() & 0o7777, 0o700);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_sorted_dynamic() {
+        #[derive(crate::Directory)]
+        struct Plugin {
+            #[file]
+            manifest_json: PathBuf,
+        }
+
+        let root: PathBuf = scratch("sorted-dynamic");
+        for name in ["c", "b", "a"] { fs::create_dir(root.join(name)).unwrap(); }
+        fs::write(root.join("c/manifest.json"), "{}").unwrap();
+        fs::write(root.join("a/manifest.json"), "{}").unwrap();
+
+        // Only complete entries are kept, in order of path
+        let plugins: SortedDynamic<Plugin> = SortedDynamic::try_init(&root).unwrap();
+        assert_eq!(plugins.keys().collect::<Vec<&PathBuf>>(), [&root.join("a"), &root.join("c")]);
+        assert_eq!(visited(&plugins, &FieldSpec::default()), [
+            (root.clone(), EntryKind::Dir, None),
+            (root.join("a/manifest.json"), EntryKind::File, None),
+            (root.join("c/manifest.json"), EntryKind::File, None),
+        ]);
+        assert!(plugins.exists());
+        assert!(plugins.validate().is_empty());
+        assert!(<SortedDynamic<PathBuf> as crate::schema::Schema>::schema().dynamic);
+
+        // Entries that disappear are reported
+        fs::remove_file(root.join("c/manifest.json")).unwrap();
+        assert!(!plugins.exists());
+        assert!(matches!(plugins.validate().as_slice(), [Problem::Missing { path, kind: EntryKind::File }] if *path == root.join("c/manifest.json")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_by_name() {
+        let root: PathBuf = scratch("by-name");
+        for name in ["case2", "case1"] { fs::write(root.join(name), "").unwrap(); }
+
+        // Entries are keyed (and sorted) by their file name only
+        let cases: ByName<PathBuf> = ByName::try_init(&root).unwrap();
+        assert_eq!(cases.keys().collect::<Vec<&OsString>>(), [OsStr::new("case1"), OsStr::new("case2")]);
+        assert_eq!(cases["case1"], root.join("case1"));
+        assert_eq!(cases.get(OsStr::new("case2")), Some(&root.join("case2")));
+        assert!(!cases.contains("case3"));
+
+        let cases: Named<PathBuf> = Named::try_init(&root).unwrap();
+        assert_eq!(cases.keys().collect::<Vec<&String>>(), ["case1", "case2"]);
+        assert_eq!(cases["case2"], root.join("case2"));
+        assert_eq!(cases.get("case1"), Some(&root.join("case1")));
+        assert!(!cases.contains("case3"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_by_name_non_utf8() {
+        use std::os::unix::ffi::OsStrExt as _;
+
+        let root: PathBuf = scratch("by-name-non-utf8");
+        let name: &OsStr = OsStr::from_bytes(b"case\xFF");
+        fs::write(root.join("case1"), "").unwrap();
+        fs::write(root.join(name), "").unwrap();
+
+        // Names that aren't UTF-8 are fine for ByName...
+        let cases: ByName<PathBuf> = ByName::try_init(&root).unwrap();
+        assert_eq!(cases[name], root.join(name));
+
+        // ...but not for Named, unless they're skipped
+        assert!(matches!(Named::<PathBuf>::try_init(&root), Err(Error::NonUtf8Name { path }) if path == root.join(name)));
+        let opts: InitOptions = InitOptions { names: NamePolicy::Skip, ..Default::default() };
+        let cases: Named<PathBuf> = Named::try_init_with(&root, &opts).unwrap();
+        assert_eq!(cases.keys().collect::<Vec<&String>>(), ["case1"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// Creates a small tree to walk recursively in the given directory.
+    fn tree(root: &Path) {
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::create_dir(root.join("target")).unwrap();
+        for file in ["a/x.txt", "a/b/y.txt", "a/b/c/z.txt", "a/b/c/z.md", "target/t.txt"] { fs::write(root.join(file), "").unwrap(); }
+    }
+
+    #[test]
+    fn test_recursive() {
+        /// Collects text files up to depth 3, skipping `target` directories.
+        struct Text;
+        impl Recursion for Text {
+            const MAX_DEPTH: Option<usize> = Some(3);
+
+            fn matches(path: &Path, _depth: usize) -> bool { path.extension().is_some_and(|e| e == "txt") }
+
+            fn prune(path: &Path, _depth: usize) -> bool { path.file_name().is_some_and(|n| n == "target") }
+        }
+
+        let root: PathBuf = scratch("recursive");
+        tree(&root);
+
+        // By default, everything in the subtree is collected
+        let all: Recursive<PathBuf> = Recursive::try_init(&root).unwrap();
+        assert_eq!(all.keys().cloned().collect::<Vec<PathBuf>>(), ["a", "a/b", "a/b/c", "a/b/c/z.md", "a/b/c/z.txt", "a/b/y.txt", "a/x.txt", "target", "target/t.txt"].map(|p| root.join(p)));
+        assert_eq!(visited(&all, &FieldSpec::default())[0], (root.clone(), EntryKind::Dir, None));
+
+        // The recursion decides what is collected and walked
+        let text: Recursive<PathBuf, Text> = Recursive::try_init(&root).unwrap();
+        assert_eq!(text.keys().cloned().collect::<Vec<PathBuf>>(), ["a/b/y.txt", "a/x.txt"].map(|p| root.join(p)));
+
+        // While name filters only apply to what is collected, not to what is walked
+        let opts: InitOptions = InitOptions::default().with_glob(&root, Patterns::new(["*.md"]));
+        let md: Recursive<PathBuf> = Recursive::try_init_with(&root, &opts).unwrap();
+        assert_eq!(md.keys().cloned().collect::<Vec<PathBuf>>(), [root.join("a/b/c/z.md")]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_recursive_cycle() {
+        let root: PathBuf = scratch("recursive-cycle");
+        tree(&root);
+        std::os::unix::fs::symlink("..", root.join("a/loop")).unwrap();
+        std::os::unix::fs::symlink("../../a", root.join("a/b/up")).unwrap();
+
+        // Symlinks to ancestors are collected, but not walked again
+        let all: Recursive<PathBuf> = Recursive::try_init(&root).unwrap();
+        assert_eq!(all.keys().cloned().collect::<Vec<PathBuf>>(), ["a", "a/b", "a/b/c", "a/b/c/z.md", "a/b/c/z.txt", "a/b/up", "a/b/y.txt", "a/loop", "a/x.txt", "target", "target/t.txt"].map(|p| root.join(p)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink() {
+        let root: PathBuf = scratch("symlink");
+        fs::write(root.join("target.txt"), "Hello, world!").unwrap();
+        std::os::unix::fs::symlink("target.txt", root.join("link")).unwrap();
+
+        // The target is read as written in the link, and resolved on request
+        let link: Symlink = Symlink::try_init(root.join("link")).unwrap();
+        assert_eq!(link.path(), root.join("link"));
+        assert_eq!(link.target(), Some(Path::new("target.txt")));
+        assert_eq!(link.resolve(), Some(fs::canonicalize(root.join("target.txt")).unwrap()));
+        assert!(link.exists());
+        assert!(link.validate().is_empty());
+
+        // A dangling link still exists, but doesn't resolve
+        fs::remove_file(root.join("target.txt")).unwrap();
+        assert!(link.exists());
+        assert_eq!(link.resolve(), None);
+        assert!(matches!(link.validate().as_slice(), [Problem::Dangling { path, target }] if *path == root.join("link") && target == Path::new("target.txt")));
+
+        // A regular file is not a symlink, and a missing path is missing
+        fs::remove_file(root.join("link")).unwrap();
+        fs::write(root.join("link"), "").unwrap();
+        assert_eq!(Symlink::try_init(root.join("link")).unwrap().target(), None);
+        assert!(!link.exists());
+        assert!(matches!(link.validate().as_slice(), [Problem::NotSymlink { path }] if *path == root.join("link")));
+        fs::remove_file(root.join("link")).unwrap();
+        assert!(matches!(link.validate().as_slice(), [Problem::Missing { path, .. }] if *path == root.join("link")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(unix, feature = "derive"))]
+    #[test]
+    fn test_symlink_inside() {
+        #[derive(crate::Directory)]
+        struct Releases {
+            #[file(symlink = "inside")]
+            current: Symlink,
+            #[dir(symlink)]
+            shared: Symlink,
+        }
+
+        let root: PathBuf = scratch("symlink-inside");
+        fs::create_dir_all(root.join("layout/v1")).unwrap();
+        fs::write(root.join("layout/v1/app"), "").unwrap();
+        fs::create_dir(root.join("elsewhere")).unwrap();
+        std::os::unix::fs::symlink("v1/app", root.join("layout/current")).unwrap();
+        std::os::unix::fs::symlink("../elsewhere", root.join("layout/shared")).unwrap();
+        let releases: Releases = Releases::try_init(root.join("layout")).unwrap();
+        assert_eq!(releases.current.target(), Some(Path::new("v1/app")));
+        assert!(releases.validate().is_empty());
+
+        // Only the link declared to stay inside may not point outside
+        fs::remove_file(root.join("layout/current")).unwrap();
+        std::os::unix::fs::symlink("../elsewhere", root.join("layout/current")).unwrap();
+        assert!(matches!(releases.validate().as_slice(), [Problem::Escapes { path, .. }] if *path == root.join("layout/current")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "cap-std", feature = "derive"))]
+    #[test]
+    fn test_opened_rename() {
+        #[derive(crate::Directory)]
+        struct Config {
+            #[file]
+            settings_json: PathBuf,
+        }
+
+        let root: PathBuf = scratch("opened-rename");
+        fs::create_dir(root.join("config")).unwrap();
+        fs::write(root.join("config/settings.json"), "original").unwrap();
+        let config: Opened<Config> = Opened::try_init(root.join("config")).unwrap();
+        assert_eq!(config.settings_json, Path::new("settings.json"));
+
+        // Swap the directory for another one on the same path
+        fs::rename(root.join("config"), root.join("moved")).unwrap();
+        fs::create_dir(root.join("config")).unwrap();
+        fs::write(root.join("config/settings.json"), "replaced").unwrap();
+
+        // Everything still resolves against the directory that was opened
+        let handle: &Dir = config.handle().unwrap();
+        assert_eq!(handle.read_to_string(&config.settings_json).unwrap(), "original");
+        fs::remove_file(root.join("config/settings.json")).unwrap();
+        assert!(config.exists());
+        assert!(config.validate().is_empty());
+        // But is reported against the original path
+        fs::remove_file(root.join("moved/settings.json")).unwrap();
+        assert!(matches!(config.validate().as_slice(), [Problem::Missing { path, .. }] if *path == root.join("config/settings.json")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
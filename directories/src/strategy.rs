@@ -0,0 +1,358 @@
+//  STRATEGY.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:32:05
+//  Last edited:
+//    18 Oct 2026, 22:32:05
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements [`proptest`] strategies that generate random on-disk
+//!   trees for a layout, either conforming to it or deliberately
+//!   violating it.
+//! 
+//!   They are derived from the [`Schema`] of a layout, and are thus
+//!   available for every layout that derives
+//!   [`Directory`](crate::Directory) with `#[directories(schema)]`:
+//!   ```rust,ignore
+//!   use directories::strategy::LayoutStrategy as _;
+//! 
+//!   #[derive(Directory)]
+//!   #[directories(schema)]
+//!   struct Workspace {
+//!       #[dir]
+//!       plugins: Dynamic<PathBuf>,
+//!   }
+//! 
+//!   proptest! {
+//!       #[test]
+//!       fn init_finds_all_plugins(tree in Workspace::conforming()) {
+//!           let dir = tempfile::tempdir().unwrap();
+//!           tree.create(dir.path()).unwrap();
+//!           prop_assert!(Workspace::init(dir.path()).validate().is_empty());
+//!       }
+//!   }
+//!   ```
+// 
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Just, Strategy};
+use proptest::sample::Index;
+
+use crate::naming::{deduce_path, default_exts, parse_ext};
+use crate::schema::{EntryKind, FieldSchema, LayoutSchema, Schema};
+use crate::testing::{Node, Tree};
+
+
+/***** CONSTANTS *****/
+/// The regular expression from which the names of dynamic entries are generated.
+const NAME: &str = "[a-z][a-z0-9_-]{0,11}(\\.[a-z]{1,4})?";
+
+/// The maximum number of entries generated in a dynamic directory.
+const MAX_ENTRIES: usize = 4;
+
+/// The maximum number of bytes generated in a file.
+const MAX_CONTENTS: usize = 64;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Joins two slash-separated paths, where either may be empty.
+/// 
+/// # Arguments
+/// - `prefix`: The path to join to.
+/// - `path`: The path to join.
+/// 
+/// # Returns
+/// The joined path.
+fn join(prefix: &str, path: &str) -> String {
+    match (prefix.is_empty(), path.is_empty()) {
+        (true, _)      => path.into(),
+        (_, true)      => prefix.into(),
+        (false, false) => format!("{prefix}/{path}"),
+    }
+}
+
+/// Returns a strategy that generates a layout's entries.
+/// 
+/// # Arguments
+/// - `schema`: The [`LayoutSchema`] of the layout.
+/// 
+/// # Returns
+/// A strategy generating the [`Generated`] entries of the layout, relative to its root.
+fn layout(schema: &LayoutSchema) -> BoxedStrategy<Generated> {
+    // Build the extensions database for this layout
+    let mut exts: Vec<(String, String)> = default_exts();
+    exts.extend(schema.exts.iter().map(|e| parse_ext(e)));
+
+    let fields: Vec<BoxedStrategy<Generated>> = schema
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let path: String = match (&field.path, field.flatten) {
+                // Absolute paths are not in the tree
+                (Some(path), _) if path.starts_with('/') => { return None; },
+                (Some(path), _) => path.trim_end_matches('/').into(),
+                (None, true)    => String::new(),
+                (None, false)   => deduce_path(&field.name, &exts),
+            };
+            Some(self::field(field, path))
+        })
+        .collect();
+    fields.prop_map(|fields| fields.into_iter().fold(Generated::default(), Generated::merge)).boxed()
+}
+
+/// Returns a strategy that generates a single field of a layout.
+/// 
+/// # Arguments
+/// - `field`: The [`FieldSchema`] of the field.
+/// - `path`: The resolved path of the field, relative to the layout.
+/// 
+/// # Returns
+/// A strategy generating the [`Generated`] entries of the field, relative to the layout.
+fn field(field: &FieldSchema, path: String) -> BoxedStrategy<Generated> {
+    if field.dynamic {
+        // Generate a random number of entries with random names
+        let (kind, nested, optional): (EntryKind, Option<LayoutSchema>, bool) = (field.kind, field.nested.clone(), field.optional);
+        return btree_set(NAME, 0..=MAX_ENTRIES)
+            .prop_flat_map(move |names| {
+                let entries: Vec<BoxedStrategy<Generated>> = names.into_iter().map(|name| entry(kind, nested.as_ref(), join(&path, &name), false)).collect();
+                let path: String = path.clone();
+                entries.prop_map(move |entries| {
+                    let dir: Generated = if path.is_empty() { Generated::default() } else { Generated::dir(path.clone()) };
+                    let mut res: Generated = entries.into_iter().fold(dir, Generated::merge);
+
+                    // Breaking an entry doesn't violate the layout, since it is then simply not collected; so only the directory itself may be removed.
+                    // (Replacing it with a file isn't a candidate either, since initializing the layout then fails instead.)
+                    res.candidates.clear();
+                    if !optional && !path.is_empty() { res.candidates.push((path.clone(), Violation::Remove)); }
+                    res
+                })
+            })
+            .boxed();
+    }
+
+    // Otherwise, generate the entry itself (if it's present)
+    let mut entry: BoxedStrategy<Generated> = entry(field.kind, field.nested.as_ref(), path.clone(), !path.is_empty());
+    if field.optional {
+        entry = (any::<bool>(), entry).prop_map(|(present, entry)| if present { entry } else { Generated::default() }).boxed();
+    } else if !path.is_empty() {
+        entry = entry.prop_map(move |mut entry| { entry.candidates.push((path.clone(), Violation::Remove)); entry }).boxed();
+    }
+    entry
+}
+
+/// Returns a strategy that generates a single entry on disk.
+/// 
+/// # Arguments
+/// - `kind`: The [`EntryKind`] of the entry.
+/// - `nested`: The [`LayoutSchema`] of the entry if it is a layout.
+/// - `path`: The path of the entry, relative to the layout.
+/// - `flip`: Whether replacing the entry with one of the other kind violates the layout.
+/// 
+/// # Returns
+/// A strategy generating the [`Generated`] entries for this entry, relative to the layout.
+fn entry(kind: EntryKind, nested: Option<&LayoutSchema>, path: String, flip: bool) -> BoxedStrategy<Generated> {
+    let mut res: Generated = Generated::default();
+    if flip && kind != EntryKind::Any { res.candidates.push((path.clone(), Violation::Flip)); }
+
+    // Layouts are directories with the nested layout in it
+    if let Some(nested) = nested {
+        return layout(nested)
+            .prop_map(move |nested| {
+                let res: Generated = if path.is_empty() { res.clone() } else { res.clone().merge(Generated::dir(path.clone())) };
+                res.merge(nested.prefix(&path))
+            })
+            .boxed();
+    }
+    let file = {
+        let (path, res): (String, Generated) = (path.clone(), res.clone());
+        vec(any::<u8>(), 0..=MAX_CONTENTS).prop_map(move |contents| res.clone().merge(Generated::file(path.clone(), contents)))
+    };
+    let dir = Just(res.merge(Generated::dir(path)));
+    match kind {
+        EntryKind::File => file.boxed(),
+        EntryKind::Dir  => dir.boxed(),
+        EntryKind::Any  => prop_oneof![file, dir].boxed(),
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Describes how an entry may be changed to violate the layout.
+#[derive(Clone, Copy, Debug)]
+enum Violation {
+    /// The entry is mandatory, so it may be removed.
+    Remove,
+    /// The entry has a declared kind, so it may be replaced by one of the other kind.
+    Flip,
+}
+
+
+
+/// The entries generated for (a part of) a layout, before they are collected in a [`Tree`].
+#[derive(Clone, Debug, Default)]
+struct Generated {
+    /// The files and (empty) directories, in the order they were generated.
+    entries    : Vec<(String, Node)>,
+    /// The entries that may be changed to violate the layout.
+    candidates : Vec<(String, Violation)>,
+}
+impl Generated {
+    /// Constructor for the Generated that contains a single file.
+    #[inline]
+    fn file(path: String, contents: Vec<u8>) -> Self { Self { entries: vec![(path, Node::File(Some(contents)))], candidates: vec![] } }
+
+    /// Constructor for the Generated that contains a single (empty) directory.
+    #[inline]
+    fn dir(path: String) -> Self { Self { entries: vec![(path, Node::Dir(Tree::new()))], candidates: vec![] } }
+
+    /// Appends the entries of another Generated to this one.
+    #[inline]
+    fn merge(mut self, other: Self) -> Self {
+        self.entries.extend(other.entries);
+        self.candidates.extend(other.candidates);
+        self
+    }
+
+    /// Moves all entries into the given directory.
+    fn prefix(mut self, prefix: &str) -> Self {
+        for (path, _) in &mut self.entries { *path = join(prefix, path); }
+        for (path, _) in &mut self.candidates { *path = join(prefix, path); }
+        self
+    }
+
+    /// Collects the entries in a [`Tree`].
+    /// 
+    /// Directories never overwrite anything that was already generated at the same path, such that overlapping fields keep their contents.
+    fn tree(&self) -> Tree {
+        let mut tree: Tree = Tree::new();
+        for (path, node) in &self.entries {
+            if matches!(node, Node::Dir(_)) && tree.get(path).is_some() { continue; }
+            tree.insert(path, node.clone());
+        }
+        tree
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns a [`proptest`] strategy that generates random trees conforming to the given layout.
+/// 
+/// Every mandatory entry is generated, optional entries are randomly present and dynamic directories get a random number of entries with random names. Files get random contents. Only the presence and kinds of entries are taken into account, so anything else (e.g., symlinks or permissions) is not reflected in the generated trees.
+/// 
+/// # Arguments
+/// - `schema`: The [`LayoutSchema`] of the layout to generate trees for.
+/// 
+/// # Returns
+/// A strategy generating [`Tree`]s, which can be created on disk with [`Tree::create()`].
+#[inline]
+pub fn conforming(schema: &LayoutSchema) -> BoxedStrategy<Tree> { layout(schema).prop_map(|gen| gen.tree()).boxed() }
+
+/// Returns a [`proptest`] strategy that generates random trees violating the given layout.
+/// 
+/// The trees are generated like with [`conforming()`], after which one of the declared entries is broken by removing it (if it is mandatory) or by replacing it with an entry of the other kind (if its kind is known). Validating a layout initialized on such a tree with [`DirectoryExt::validate()`](crate::DirectoryExt::validate()) thus reports at least one problem. The entries of dynamic directories are never broken, since those would simply not be collected; the directory itself is removed instead.
+/// 
+/// Note that a layout without any mandatory entries or entries of a known kind cannot be violated, in which case every generated tree is rejected.
+/// 
+/// # Arguments
+/// - `schema`: The [`LayoutSchema`] of the layout to generate trees for.
+/// 
+/// # Returns
+/// A strategy generating [`Tree`]s, which can be created on disk with [`Tree::create()`].
+pub fn violating(schema: &LayoutSchema) -> BoxedStrategy<Tree> {
+    (layout(schema), any::<Index>())
+        .prop_filter_map("layout has nothing to violate", |(gen, index)| {
+            if gen.candidates.is_empty() { return None; }
+            let (path, violation): &(String, Violation) = index.get(&gen.candidates);
+
+            // Apply the violation
+            let mut tree: Tree = gen.tree();
+            let node: Option<Node> = tree.remove(path);
+            if let (Violation::Flip, Some(node)) = (violation, node) {
+                match node {
+                    Node::File(_) => tree.insert(path, Node::Dir(Tree::new())),
+                    Node::Dir(_)  => tree.insert(path, Node::File(Some(vec![]))),
+                }
+            }
+            Some(tree)
+        })
+        .boxed()
+}
+
+
+
+/// Provides [`proptest`] strategies for generating random trees for a layout.
+/// 
+/// This is automatically implemented for everything that implements [`Schema`], including every layout that derives [`Directory`](crate::Directory) with `#[directories(schema)]`. Types that are not layouts (e.g., a [`PathBuf`](std::path::PathBuf)) are treated as an empty layout.
+pub trait LayoutStrategy: Schema {
+    /// Returns a strategy that generates random trees conforming to this layout.
+    /// 
+    /// See [`conforming()`] for more information.
+    #[inline]
+    fn conforming() -> BoxedStrategy<Tree> { conforming(&Self::schema().nested.unwrap_or_default()) }
+
+    /// Returns a strategy that generates random trees violating this layout.
+    /// 
+    /// See [`violating()`] for more information.
+    #[inline]
+    fn violating() -> BoxedStrategy<Tree> { violating(&Self::schema().nested.unwrap_or_default()) }
+}
+impl<T: Schema> LayoutStrategy for T {}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use proptest::strategy::ValueTree as _;
+    use proptest::test_runner::{Config, TestRunner};
+
+    use super::*;
+    use crate::schema::EntrySchema;
+
+
+    #[test]
+    fn test_violating_dynamic() {
+        // A layout with only a dynamic directory of which every entry has a mandatory file
+        let entry: LayoutSchema = LayoutSchema {
+            fields: vec![FieldSchema::new("conf", Some("conf".into()), EntrySchema { kind: EntryKind::File, ..Default::default() })],
+            ..Default::default()
+        };
+        let schema: LayoutSchema = LayoutSchema {
+            fields: vec![FieldSchema::new("items", Some("items".into()), EntrySchema {
+                kind: EntryKind::Dir,
+                dynamic: true,
+                nested: Some(entry),
+                ..Default::default()
+            })],
+            ..Default::default()
+        };
+
+        // Removing the `conf` of an entry only makes it not collected, so the directory itself must be removed
+        let mut runner: TestRunner = TestRunner::deterministic();
+        let strategy: BoxedStrategy<Tree> = violating(&schema);
+        for _ in 0..64 {
+            let tree: Tree = strategy.new_tree(&mut runner).unwrap().current();
+            assert!(tree.get("items").is_none(), "Violating tree still has 'items': {tree:?}");
+        }
+
+        // It cannot be violated if the directory is optional
+        let mut schema: LayoutSchema = schema;
+        schema.fields[0].optional = true;
+        let mut runner: TestRunner = TestRunner::new_with_rng(Config { max_local_rejects: 16, ..Config::default() }, runner.new_rng());
+        assert!(violating(&schema).new_tree(&mut runner).is_err());
+    }
+}
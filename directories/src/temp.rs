@@ -0,0 +1,288 @@
+//  TEMP.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:22:25
+//  Last edited:
+//    18 Oct 2026, 22:22:25
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements [`TempLayout`], which instantiates a layout in a fresh
+//!   temporary directory that is removed again when it is dropped. This
+//!   is mostly useful for tests that need a fixture tree on disk.
+//! 
+//!   For example:
+//!   ```rust,ignore
+//!   let layout: TempLayout<Workspace> = TempLayout::builder()
+//!       .entry::<Plugin>("plugins/hello")
+//!       .file("config.toml", "verbose = true\n")
+//!       .build()?;
+//!   assert!(layout.exists());
+//!   ```
+// 
+
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::fs;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::directory::{DirectoryExt, Error};
+use crate::template::Context;
+
+
+/***** AUXILLARY *****/
+/// Shorthand for the things a [`TempLayoutBuilder`] does to the temporary root after scaffolding the layout.
+type Populate = Box<dyn FnOnce(&Path, &Context) -> Result<(), Error>>;
+
+/// Builds a [`TempLayout`] with additional files, directories or dynamic entries.
+pub struct TempLayoutBuilder<T> {
+    /// The context to render any templates with.
+    ctx      : Context,
+    /// The things to do to the root after the layout has been scaffolded, in order.
+    populate : Vec<Populate>,
+    /// Remembers the layout we build.
+    _layout  : PhantomData<T>,
+}
+impl<T> Default for TempLayoutBuilder<T> {
+    #[inline]
+    fn default() -> Self { Self { ctx: Context::default(), populate: vec![], _layout: PhantomData } }
+}
+impl<T> Debug for TempLayoutBuilder<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        f.debug_struct("TempLayoutBuilder").field("ctx", &self.ctx).field("populate", &self.populate.len()).finish()
+    }
+}
+impl<T: DirectoryExt> TempLayoutBuilder<T> where Error: From<T::Error> {
+    /// Sets the context to render any templates in the layout with.
+    /// 
+    /// # Arguments
+    /// - `ctx`: The [`Context`] to render templates with.
+    /// 
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn context(mut self, ctx: Context) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    /// Adds a file with the given contents, e.g., to populate an optional field.
+    /// 
+    /// Any parent directories are created as needed, and existing files are overwritten.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file, relative to the root.
+    /// - `contents`: The contents of the file.
+    /// 
+    /// # Returns
+    /// Self for chaining.
+    pub fn file(mut self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        let (path, contents): (PathBuf, Vec<u8>) = (path.into(), contents.into());
+        self.populate.push(Box::new(move |root: &Path, _: &Context| {
+            let path: PathBuf = root.join(path);
+            if let Some(parent) = path.parent() {
+                if let Err(err) = fs::create_dir_all(parent) { return Err(Error::DirCreate { path: parent.into(), err }); }
+            }
+            fs::write(&path, contents).map_err(|err| Error::FileWrite { path, err })
+        }));
+        self
+    }
+
+    /// Adds an (empty) directory, e.g., to populate an optional field.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the directory, relative to the root.
+    /// 
+    /// # Returns
+    /// Self for chaining.
+    pub fn dir(mut self, path: impl Into<PathBuf>) -> Self {
+        let path: PathBuf = path.into();
+        self.populate.push(Box::new(move |root: &Path, _: &Context| {
+            let path: PathBuf = root.join(path);
+            fs::create_dir_all(&path).map_err(|err| Error::DirCreate { path, err })
+        }));
+        self
+    }
+
+    /// Adds an entry to a dynamic field (e.g., a [`Dynamic<U>`](crate::std::Dynamic)), which is scaffolded like the layout itself.
+    /// 
+    /// # Generic arguments
+    /// - `U`: The type of the entry.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the entry, relative to the root (e.g., `plugins/hello`).
+    /// 
+    /// # Returns
+    /// Self for chaining.
+    pub fn entry<U: DirectoryExt + 'static>(mut self, path: impl Into<PathBuf>) -> Self where Error: From<U::Error> {
+        let path: PathBuf = path.into();
+        self.populate.push(Box::new(move |root: &Path, ctx: &Context| {
+            let path: PathBuf = root.join(path);
+            fs::create_dir_all(&path).map_err(|err| Error::DirCreate { path: path.clone(), err })?;
+            U::try_init(path)?.ensure_with(ctx)
+        }));
+        self
+    }
+
+    /// Creates the temporary root, scaffolds the layout in it and adds anything given to this builder.
+    /// 
+    /// # Returns
+    /// A new [`TempLayout`] with the layout initialized in the temporary root.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create the temporary root, or failed to create or initialize anything in it.
+    pub fn build(self) -> Result<TempLayout<T>, Error> {
+        let dir: TempDir = match TempDir::new() {
+            Ok(dir) => dir,
+            Err(err) => { return Err(Error::DirCreate { path: std::env::temp_dir(), err }); },
+        };
+
+        // Scaffold the layout itself
+        T::try_init(dir.path())?.ensure_with(&self.ctx)?;
+
+        // Then add the additional things
+        for populate in self.populate {
+            populate(dir.path(), &self.ctx)?;
+        }
+
+        // Finally, (re-)initialize the layout to find everything
+        let layout: T = T::try_init(dir.path())?;
+        Ok(TempLayout { layout, dir })
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Instantiates a layout in a fresh temporary directory, which is removed again when this TempLayout is dropped.
+/// 
+/// All mandatory files and directories in the layout are created (see [`DirectoryExt::ensure()`]), after which it derefs to the initialized layout. Use [`TempLayout::builder()`] to add optional files or dynamic entries.
+#[derive(Debug)]
+pub struct TempLayout<T> {
+    /// The initialized layout.
+    layout : T,
+    /// The temporary root, which is removed on drop.
+    dir    : TempDir,
+}
+impl<T: DirectoryExt> TempLayout<T> where Error: From<T::Error> {
+    /// Constructor for the TempLayout that scaffolds the layout in a fresh temporary directory.
+    /// 
+    /// # Returns
+    /// A new TempLayout with the layout initialized in the temporary root.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create the temporary root, or failed to create or initialize anything in it.
+    #[inline]
+    pub fn new() -> Result<Self, Error> { Self::builder().build() }
+
+    /// Returns a builder to add additional files, directories or dynamic entries before initializing the layout.
+    #[inline]
+    pub fn builder() -> TempLayoutBuilder<T> { TempLayoutBuilder::default() }
+
+    /// Re-initializes the layout, e.g., to find dynamic entries that were created after it was built.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to initialize the layout.
+    #[inline]
+    pub fn reinit(&mut self) -> Result<(), Error> {
+        self.layout = T::try_init(self.dir.path())?;
+        Ok(())
+    }
+}
+impl<T> TempLayout<T> {
+    /// Returns the path of the temporary root.
+    #[inline]
+    pub fn path(&self) -> &Path { self.dir.path() }
+}
+
+impl<T> Deref for TempLayout<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { &self.layout }
+}
+impl<T> DerefMut for TempLayout<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.layout }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+    use crate::std::Dynamic;
+
+    #[derive(crate::Directory)]
+    struct Plugin {
+        #[file]
+        manifest_json : PathBuf,
+    }
+
+    #[derive(crate::Directory)]
+    struct Workspace {
+        #[file]
+        config_json   : PathBuf,
+        #[file]
+        settings_json : Option<PathBuf>,
+        #[dir]
+        plugins       : Dynamic<Plugin>,
+    }
+
+
+    #[test]
+    fn test_new() {
+        let layout: TempLayout<Workspace> = TempLayout::new().unwrap();
+        let root: PathBuf = layout.path().into();
+
+        // Only the mandatory entries are scaffolded
+        assert_eq!(layout.config_json, root.join("config.json"));
+        assert!(layout.config_json.is_file());
+        assert!(layout.plugins.is_empty());
+        assert!(layout.settings_json.is_none());
+        assert!(layout.validate().is_empty());
+
+        // And everything is gone once it's dropped
+        drop(layout);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut layout: TempLayout<Workspace> = TempLayout::builder()
+            .file("settings.json", "{}")
+            .entry::<Plugin>("plugins/hello")
+            .dir("plugins/empty")
+            .build()
+            .unwrap();
+        let root: PathBuf = layout.path().into();
+
+        // Optional files are found, and dynamic entries are scaffolded (unlike plain directories, which are no complete plugin)
+        assert_eq!(fs::read_to_string(layout.settings_json.as_ref().unwrap()).unwrap(), "{}");
+        assert_eq!(layout.plugins.keys().collect::<Vec<&PathBuf>>(), [&root.join("plugins/hello")]);
+        assert!(root.join("plugins/hello/manifest.json").is_file());
+        assert!(layout.validate().is_empty());
+
+        // Entries created afterwards are found after re-initializing
+        fs::write(root.join("plugins/empty/manifest.json"), "{}").unwrap();
+        assert_eq!(layout.plugins.len(), 1);
+        layout.reinit().unwrap();
+        assert_eq!(layout.plugins.len(), 2);
+    }
+
+    #[test]
+    fn test_builder_fails() {
+        // The layout already has a file where the directory should go
+        assert!(matches!(TempLayout::<Workspace>::builder().dir("config.json").build(), Err(Error::DirCreate { .. })));
+    }
+}
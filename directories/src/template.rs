@@ -0,0 +1,198 @@
+//  TEMPLATE.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:08:32
+//  Last edited:
+//    18 Oct 2026, 22:08:32
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements a minimal template language for the default contents of
+//!   files, as given by `#[file(template = "...")]`.
+//! 
+//!   Templates are plain text in which `{{ name }}` is replaced with the
+//!   value of the variable `name` in a [`Context`]. A literal `{{` can be
+//!   written as `{{{{`. Besides the variables in the context, the
+//!   following built-in variables are available:
+//!   - `path`: The absolute path of the file being rendered.
+//!   - `name`: The file name of the file being rendered.
+//!   - `root`: The root directory of the layout, if given with
+//!     [`Context::new()`].
+// 
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+
+
+/***** ERRORS *****/
+/// Defines errors that may occur when rendering a template.
+#[derive(Debug)]
+pub enum Error {
+    /// A `{{` was not closed by a `}}`.
+    Unterminated { pos: usize },
+    /// A variable was used that is neither built-in nor in the context.
+    UnknownVariable { name: String },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            Unterminated { pos }     => write!(f, "Unterminated '{{{{' at byte {pos}"),
+            UnknownVariable { name } => write!(f, "Unknown variable '{name}'"),
+        }
+    }
+}
+impl error::Error for Error {}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the variables available to templates, on top of the built-in ones.
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    /// The root directory of the layout, which is available as `root`.
+    root : Option<PathBuf>,
+    /// The user-defined variables.
+    vars : HashMap<String, String>,
+}
+impl Context {
+    /// Constructor for the Context that makes the given root directory available as `root`.
+    /// 
+    /// Use [`Context::default()`] if you don't want to give a root.
+    /// 
+    /// # Arguments
+    /// - `root`: The root directory of the layout (typically the `base` it was initialized with).
+    /// 
+    /// # Returns
+    /// A new Context instance without any user-defined variables.
+    #[inline]
+    pub fn new(root: impl Into<PathBuf>) -> Self { Self { root: Some(root.into()), vars: HashMap::new() } }
+
+    /// Adds a variable to this context, overriding any existing variable of the same name.
+    /// 
+    /// Note that built-in variables cannot be overridden.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the variable.
+    /// - `value`: The value of the variable.
+    /// 
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Adds a variable to this context, overriding any existing variable of the same name.
+    /// 
+    /// Note that built-in variables cannot be overridden.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the variable.
+    /// - `value`: The value of the variable.
+    #[inline]
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) { self.vars.insert(name.into(), value.into()); }
+
+    /// Returns the root directory given to this context, if any.
+    #[inline]
+    pub fn root(&self) -> Option<&Path> { self.root.as_deref() }
+
+
+
+    /// Renders the given template for the file at the given path.
+    /// 
+    /// # Arguments
+    /// - `template`: The template to render.
+    /// - `path`: The path of the file that is rendered, for the built-in variables. If it's relative, it is made absolute (relative to the current working directory) for `path`.
+    /// 
+    /// # Returns
+    /// The rendered template.
+    /// 
+    /// # Errors
+    /// This function errors if the template is malformed or uses an unknown variable.
+    pub fn render(&self, template: &str, path: &Path) -> Result<String, Error> {
+        let mut res: String = String::with_capacity(template.len());
+        let mut rem: &str = template;
+        while let Some(start) = rem.find("{{") {
+            res.push_str(&rem[..start]);
+            rem = &rem[start + 2..];
+
+            // Escaped braces
+            if let Some(next) = rem.strip_prefix("{{") {
+                res.push_str("{{");
+                rem = next;
+                continue;
+            }
+
+            // Otherwise, find the variable
+            let end: usize = match rem.find("}}") {
+                Some(end) => end,
+                None      => { return Err(Error::Unterminated { pos: template.len() - rem.len() - 2 }); },
+            };
+            let name: &str = rem[..end].trim();
+            match (name, &self.root) {
+                ("path", _)          => res.push_str(&std::path::absolute(path).unwrap_or_else(|_| path.into()).display().to_string()),
+                ("name", _)          => res.push_str(&path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()),
+                ("root", Some(root)) => res.push_str(&root.display().to_string()),
+                (name, _)            => match self.vars.get(name) {
+                    Some(value) => res.push_str(value),
+                    None        => { return Err(Error::UnknownVariable { name: name.into() }); },
+                },
+            }
+            rem = &rem[end + 2..];
+        }
+        res.push_str(rem);
+        Ok(res)
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_variables() {
+        let ctx: Context = Context::new("/srv/app").with("user", "amy");
+        assert_eq!(ctx.render("Hello {{ user }}, {{name}} in {{ root }}!", Path::new("/srv/app/README.md")).unwrap(), "Hello amy, README.md in /srv/app!");
+        assert_eq!(ctx.render("No variables at all", Path::new("/srv/app/a")).unwrap(), "No variables at all");
+        assert_eq!(ctx.render("", Path::new("/srv/app/a")).unwrap(), "");
+    }
+
+    #[test]
+    fn test_render_path_absolute() {
+        let path: String = Context::default().render("{{ path }}", Path::new("relative/file.txt")).unwrap();
+        assert!(Path::new(&path).is_absolute(), "'{path}' is not absolute");
+        assert!(path.ends_with("relative/file.txt"));
+        assert_eq!(Context::default().render("{{ path }}", Path::new("/abs/file.txt")).unwrap(), "/abs/file.txt");
+    }
+
+    #[test]
+    fn test_render_escapes() {
+        let ctx: Context = Context::default().with("x", "1");
+        assert_eq!(ctx.render("{{{{ x }}", Path::new("/a")).unwrap(), "{{ x }}");
+        assert_eq!(ctx.render("{{{{{{ x }}", Path::new("/a")).unwrap(), "{{1");
+        assert_eq!(ctx.render("}} {{x}} }}", Path::new("/a")).unwrap(), "}} 1 }}");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let ctx: Context = Context::default();
+        assert!(matches!(ctx.render("abc {{ x", Path::new("/a")), Err(Error::Unterminated { pos: 4 })));
+        assert!(matches!(ctx.render("{{{{ {{ x", Path::new("/a")), Err(Error::Unterminated { pos: 5 })));
+        assert!(matches!(ctx.render("{{ nope }}", Path::new("/a")), Err(Error::UnknownVariable { name }) if name == "nope"));
+        // Without a root in the context, `root` is just an unknown variable
+        assert!(matches!(ctx.render("{{ root }}", Path::new("/a")), Err(Error::UnknownVariable { name }) if name == "root"));
+    }
+}
@@ -0,0 +1,514 @@
+//  TESTING.rs
+//    by Lut99
+// 
+//  Created:
+//    18 Oct 2026, 22:24:09
+//  Last edited:
+//    18 Oct 2026, 22:32:05
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Provides utilities for testing code that uses layouts.
+//! 
+//!   Fixture trees can be built with the [`tree!`](crate::tree!) macro,
+//!   and then either be created on disk ([`Tree::create()`]) or compared
+//!   against what is on disk ([`assert_layout_matches!`](crate::assert_layout_matches!)).
+//!   In addition, [`assert_layout_exists!`](crate::assert_layout_exists!)
+//!   asserts a layout exists and reports exactly which paths are wrong if
+//!   it doesn't.
+//! 
+//!   With the `insta`-feature enabled, `assert_layout_snapshot!` snapshots
+//!   the rendering of a layout (see [`DirectoryExt::render()`]) with
+//!   [`insta`].
+//! 
+//!   For example:
+//!   ```rust,ignore
+//!   use directories::{assert_layout_exists, assert_layout_matches, tree};
+//! 
+//!   tree! {
+//!       "Test.txt" => "Hello, world!\n",
+//!       "HelloWorld/" {
+//!           "file.world",
+//!       },
+//!   }.create(&root)?;
+//!   let root_dir = RootDir::init(&root);
+//!   assert_layout_exists!(root_dir);
+//!   assert_layout_matches!(root_dir.path, tree! { "Test.txt", "HelloWorld/" { "file.world" } });
+//!   ```
+// 
+
+use std::collections::BTreeMap;
+use std::fmt::Arguments;
+use std::fs::{self, DirEntry, FileType, ReadDir};
+use std::path::{Path, PathBuf};
+
+use crate::directory::{DirectoryExt, Error, Problem};
+
+// Re-export insta for use in our macros
+#[cfg(feature = "insta")]
+#[doc(hidden)]
+pub use insta;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads the given directory as a [`Tree`], without following symlinks.
+/// 
+/// # Arguments
+/// - `path`: The directory to read.
+/// 
+/// # Returns
+/// A new [`Tree`] with everything in the directory.
+/// 
+/// # Errors
+/// This function errors if we failed to read the directory or any of its entries.
+fn read_tree(path: &Path) -> Result<Tree, Error> {
+    let entries: ReadDir = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err)    => { return Err(Error::DirRead { path: path.into(), err }); },
+    };
+    let mut tree: Tree = Tree::new();
+    for (i, entry) in entries.enumerate() {
+        let entry: DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Error::DirEntryRead { path: path.into(), entry: i, err }); },
+        };
+        let ty: FileType = match entry.file_type() {
+            Ok(ty)   => ty,
+            Err(err) => { return Err(Error::DirEntryRead { path: path.into(), entry: i, err }); },
+        };
+        let name: String = entry.file_name().to_string_lossy().into_owned();
+        let node: Node = if ty.is_dir() {
+            Node::Dir(read_tree(&entry.path())?)
+        } else if ty.is_symlink() {
+            Node::File(None)
+        } else {
+            match fs::read(entry.path()) {
+                Ok(bytes) => Node::File(Some(bytes)),
+                Err(err)  => { return Err(Error::FileRead { path: entry.path(), err }); },
+            }
+        };
+        tree.0.insert(name, node);
+    }
+    Ok(tree)
+}
+
+/// Compares an expected [`Tree`] with an actual one.
+/// 
+/// # Arguments
+/// - `prefix`: The path of the trees relative to the root, for reporting.
+/// - `expected`: The [`Tree`] that we expect.
+/// - `actual`: The [`Tree`] that we found.
+/// - `diffs`: A list to push the differences to.
+fn diff_tree(prefix: &Path, expected: &Tree, actual: &Tree, diffs: &mut Vec<String>) {
+    for (name, node) in &expected.0 {
+        let path: PathBuf = prefix.join(name);
+        match (node, actual.0.get(name)) {
+            (Node::File(_), None) => diffs.push(format!("Missing file '{}'", path.display())),
+            (Node::Dir(_), None)  => diffs.push(format!("Missing directory '{}'", path.display())),
+
+            (Node::File(_), Some(Node::Dir(_))) => diffs.push(format!("'{}' is a directory, expected a file", path.display())),
+            (Node::Dir(_), Some(Node::File(_))) => diffs.push(format!("'{}' is a file, expected a directory", path.display())),
+
+            (Node::File(Some(expected)), Some(Node::File(Some(actual)))) if expected != actual => diffs.push(format!("'{}' has different contents", path.display())),
+            (Node::File(_), Some(Node::File(_))) => {},
+            (Node::Dir(expected), Some(Node::Dir(actual))) => diff_tree(&path, expected, actual, diffs),
+        }
+    }
+    for (name, node) in &actual.0 {
+        if expected.0.contains_key(name) { continue; }
+        let path: PathBuf = prefix.join(name);
+        match node {
+            Node::File(_) => diffs.push(format!("Unexpected file '{}'", path.display())),
+            Node::Dir(_)  => diffs.push(format!("Unexpected directory '{}'", path.display())),
+        }
+    }
+}
+
+/// Formats the given list of complaints as a panic message.
+/// 
+/// # Arguments
+/// - `what`: What was wrong in general.
+/// - `items`: The specific things that were wrong.
+/// - `msg`: An optional message given by the user.
+/// 
+/// # Returns
+/// The message to panic with.
+fn report(what: String, items: &[String], msg: Option<Arguments>) -> String {
+    let mut res: String = what;
+    if let Some(msg) = msg { res.push_str(&format!(": {msg}")); }
+    for item in items {
+        res.push_str(&format!("\n - {item}"));
+    }
+    res
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Asserts that the given layout exists, and panics with all of its problems if it doesn't.
+/// 
+/// This is the implementation of [`assert_layout_exists!`](crate::assert_layout_exists!).
+/// 
+/// # Arguments
+/// - `layout`: The layout to check.
+/// - `msg`: An optional message to add to the panic.
+/// 
+/// # Panics
+/// This function panics if [`DirectoryExt::validate()`] reports any [`Problem`]s, or if [`DirectoryExt::exists()`] returns false.
+#[track_caller]
+pub fn assert_exists<T: DirectoryExt>(layout: &T, msg: Option<Arguments>) {
+    let problems: Vec<Problem> = layout.validate();
+    if !problems.is_empty() {
+        panic!("{}", report(format!("Layout does not exist ({} problem{})", problems.len(), if problems.len() == 1 { "" } else { "s" }), &problems.iter().map(Problem::to_string).collect::<Vec<String>>(), msg));
+    }
+    if !layout.exists() {
+        panic!("{}", report("Layout does not exist (but no specific paths were found to be wrong)".into(), &[], msg));
+    }
+}
+
+/// Asserts that the given directory on disk matches the given [`Tree`] exactly, and panics with all of the differences if it doesn't.
+/// 
+/// This is the implementation of [`assert_layout_matches!`](crate::assert_layout_matches!).
+/// 
+/// # Arguments
+/// - `root`: The directory to check.
+/// - `tree`: The [`Tree`] it should match.
+/// - `msg`: An optional message to add to the panic.
+/// 
+/// # Panics
+/// This function panics if the directory does not match the tree, or if we failed to read it.
+#[track_caller]
+pub fn assert_matches(root: impl AsRef<Path>, tree: &Tree, msg: Option<Arguments>) {
+    let root: &Path = root.as_ref();
+    let diffs: Vec<String> = match tree.diff(root) {
+        Ok(diffs) => diffs,
+        Err(err)  => panic!("{}", report(format!("Failed to read '{}': {err}", root.display()), &[], msg)),
+    };
+    if !diffs.is_empty() {
+        panic!("{}", report(format!("Directory '{}' does not match the expected tree ({} difference{})", root.display(), diffs.len(), if diffs.len() == 1 { "" } else { "s" }), &diffs, msg));
+    }
+}
+
+
+
+/// Defines a single node in a [`Tree`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Node {
+    /// It's a file, with optionally its contents. If the contents are [`None`], they are not compared and the file is created empty.
+    File(Option<Vec<u8>>),
+    /// It's a directory with the given nested tree.
+    Dir(Tree),
+}
+
+
+
+/// Defines a tree of files and directories, e.g., as a fixture for tests.
+/// 
+/// Use the [`tree!`](crate::tree!) macro to build it conveniently.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Tree(BTreeMap<String, Node>);
+impl Tree {
+    /// Constructor for the Tree that initializes it as empty.
+    /// 
+    /// # Returns
+    /// A new Tree without any files or directories in it.
+    #[inline]
+    pub fn new() -> Self { Self(BTreeMap::new()) }
+
+    /// Reads a Tree from disk, including the contents of all files.
+    /// 
+    /// Symlinks are not followed, and are read as files without contents.
+    /// 
+    /// # Arguments
+    /// - `root`: The directory to read.
+    /// 
+    /// # Returns
+    /// A new Tree with everything in the directory.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the directory or any of its entries.
+    #[inline]
+    pub fn read(root: impl AsRef<Path>) -> Result<Self, Error> { read_tree(root.as_ref()) }
+
+    /// Adds a node to this tree, overwriting any node at the same path.
+    /// 
+    /// The path may contain slashes, in which case any missing intermediate directories are added too. If it ends in a slash and the node is a file, an empty directory is added instead.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the node, relative to the root of this tree.
+    /// - `node`: The [`Node`] to add.
+    pub fn insert(&mut self, path: &str, node: Node) {
+        let (path, node): (&str, Node) = match path.strip_suffix('/') {
+            Some(path) if matches!(node, Node::File(_)) => (path, Node::Dir(Tree::new())),
+            Some(path) => (path, node),
+            None => (path, node),
+        };
+        match path.split_once('/') {
+            Some((first, rest)) => {
+                let nested: &mut Node = self.0.entry(first.into()).or_insert_with(|| Node::Dir(Tree::new()));
+                if !matches!(nested, Node::Dir(_)) { *nested = Node::Dir(Tree::new()); }
+                if let Node::Dir(nested) = nested { nested.insert(rest, node); }
+            },
+            None => { self.0.insert(path.into(), node); },
+        }
+    }
+
+    /// Returns the node at the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the node, relative to the root of this tree. May contain slashes to refer to nested nodes.
+    /// 
+    /// # Returns
+    /// The [`Node`], or [`None`] if there is nothing at that path.
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        let path: &str = path.trim_end_matches('/');
+        match path.split_once('/') {
+            Some((first, rest)) => match self.0.get(first) {
+                Some(Node::Dir(nested)) => nested.get(rest),
+                _                       => None,
+            },
+            None => self.0.get(path),
+        }
+    }
+
+    /// Removes the node at the given path.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the node, relative to the root of this tree. May contain slashes to refer to nested nodes.
+    /// 
+    /// # Returns
+    /// The removed [`Node`], or [`None`] if there was nothing at that path.
+    pub fn remove(&mut self, path: &str) -> Option<Node> {
+        let path: &str = path.trim_end_matches('/');
+        match path.split_once('/') {
+            Some((first, rest)) => match self.0.get_mut(first) {
+                Some(Node::Dir(nested)) => nested.remove(rest),
+                _                       => None,
+            },
+            None => self.0.remove(path),
+        }
+    }
+
+    /// Returns an iterator over the names and [`Node`]s directly in this tree, sorted by name.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Node)> { self.0.iter().map(|(n, e)| (n.as_str(), e)) }
+
+
+
+    /// Creates this tree on disk, overwriting any files that already exist.
+    /// 
+    /// # Arguments
+    /// - `root`: The directory to create the tree in. It is created if it does not exist.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to create any of the files or directories.
+    pub fn create(&self, root: impl AsRef<Path>) -> Result<(), Error> {
+        let root: &Path = root.as_ref();
+        if let Err(err) = fs::create_dir_all(root) { return Err(Error::DirCreate { path: root.into(), err }); }
+        for (name, node) in &self.0 {
+            let path: PathBuf = root.join(name);
+            match node {
+                Node::File(contents) => if let Err(err) = fs::write(&path, contents.as_deref().unwrap_or_default()) { return Err(Error::FileWrite { path, err }); },
+                Node::Dir(nested)    => nested.create(path)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares this tree with what is on disk.
+    /// 
+    /// The contents of files are only compared if they are given in this tree.
+    /// 
+    /// # Arguments
+    /// - `root`: The directory to compare with.
+    /// 
+    /// # Returns
+    /// A list of human-readable differences, relative to the `root`. If it's empty, the directory matches this tree.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the directory or any of its entries.
+    pub fn diff(&self, root: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let actual: Tree = read_tree(root.as_ref())?;
+        let mut diffs: Vec<String> = vec![];
+        diff_tree(Path::new(""), self, &actual, &mut diffs);
+        Ok(diffs)
+    }
+}
+
+
+
+
+
+/***** MACROS *****/
+/// Builds a [`Tree`](crate::testing::Tree) of files and directories.
+/// 
+/// Entries are separated by commas, and can be:
+/// - `"name"`: A file of which the contents don't matter (created empty);
+/// - `"name" => contents`: A file with the given contents (anything that converts into a `Vec<u8>`, like a `&str`);
+/// - `"name/"`: An empty directory; or
+/// - `"name" { ... }` (or `"name/" { ... }`): A directory with the given entries.
+/// 
+/// Names may contain slashes to refer to nested entries directly.
+/// 
+/// # Example
+/// ```rust,ignore
+/// use directories::tree;
+/// 
+/// let tree = tree! {
+///     "Test.txt" => "Hello, world!\n",
+///     "HelloWorld/" {
+///         "file.world",
+///         "nested/",
+///     },
+/// };
+/// assert!(tree.get("HelloWorld/nested").is_some());
+/// ```
+#[macro_export]
+macro_rules! tree {
+    (@entries $tree:ident;) => {};
+    (@entries $tree:ident; $name:literal { $($nested:tt)* } $(, $($rest:tt)*)?) => {
+        $tree.insert($name, $crate::testing::Node::Dir($crate::tree! { $($nested)* }));
+        $crate::tree!(@entries $tree; $($($rest)*)?);
+    };
+    (@entries $tree:ident; $name:literal => $contents:expr $(, $($rest:tt)*)?) => {
+        $tree.insert($name, $crate::testing::Node::File(::std::option::Option::Some(::std::convert::Into::<::std::vec::Vec<u8>>::into($contents))));
+        $crate::tree!(@entries $tree; $($($rest)*)?);
+    };
+    (@entries $tree:ident; $name:literal $(, $($rest:tt)*)?) => {
+        $tree.insert($name, $crate::testing::Node::File(::std::option::Option::None));
+        $crate::tree!(@entries $tree; $($($rest)*)?);
+    };
+
+    ($($entries:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut tree: $crate::testing::Tree = $crate::testing::Tree::new();
+        $crate::tree!(@entries tree; $($entries)*);
+        tree
+    }};
+}
+
+/// Asserts that a layout exists, i.e., that all of its paths exist and are as declared.
+/// 
+/// If it doesn't, it panics with every [`Problem`](crate::Problem) found by [`DirectoryExt::validate()`](crate::DirectoryExt::validate()), so you know exactly which paths were wrong.
+/// 
+/// Like [`assert!`], an additional message may be given after the layout.
+/// 
+/// # Example
+/// ```rust,ignore
+/// let root_dir = RootDir::init(&root);
+/// assert_layout_exists!(root_dir);
+/// assert_layout_exists!(root_dir, "after creating {}", root.display());
+/// ```
+#[macro_export]
+macro_rules! assert_layout_exists {
+    ($layout:expr $(,)?) => {
+        $crate::testing::assert_exists(&$layout, ::std::option::Option::None)
+    };
+    ($layout:expr, $($arg:tt)+) => {
+        $crate::testing::assert_exists(&$layout, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Asserts that a directory on disk matches a [`Tree`](crate::testing::Tree) exactly, e.g., one built with [`tree!`](crate::tree!).
+/// 
+/// If it doesn't, it panics with every missing, unexpected or different path.
+/// 
+/// The directory can be anything that implements `AsRef<Path>`, such as the `#[this]` field of a layout. Like [`assert!`], an additional message may be given after the tree.
+/// 
+/// # Example
+/// ```rust,ignore
+/// assert_layout_matches!(root_dir.path, tree! { "Test.txt", "HelloWorld/" { "file.world" } });
+/// ```
+#[macro_export]
+macro_rules! assert_layout_matches {
+    ($root:expr, $tree:expr $(,)?) => {
+        $crate::testing::assert_matches(&$root, &$tree, ::std::option::Option::None)
+    };
+    ($root:expr, $tree:expr, $($arg:tt)+) => {
+        $crate::testing::assert_matches(&$root, &$tree, ::std::option::Option::Some(::std::format_args!($($arg)+)))
+    };
+}
+
+/// Snapshots a layout with [`insta`](https://insta.rs), using its rendering by [`DirectoryExt::render()`](crate::DirectoryExt::render()).
+/// 
+/// The layout is rendered relative to the given root, so the snapshot does not depend on where the layout lives (e.g., in a temporary directory). Like [`insta::assert_snapshot!`], a name for the snapshot may be given first.
+/// 
+/// # Example
+/// ```rust,ignore
+/// let root_dir = RootDir::init(&root);
+/// assert_layout_snapshot!(root_dir, &root);
+/// assert_layout_snapshot!("after_build", root_dir, &root);
+/// ```
+#[cfg(feature = "insta")]
+#[macro_export]
+macro_rules! assert_layout_snapshot {
+    ($name:literal, $layout:expr, $root:expr $(,)?) => {
+        $crate::testing::insta::assert_snapshot!($name, $crate::DirectoryExt::render(&$layout, $root))
+    };
+    ($layout:expr, $root:expr $(,)?) => {
+        $crate::testing::insta::assert_snapshot!($crate::DirectoryExt::render(&$layout, $root))
+    };
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let root: PathBuf = std::env::temp_dir().join(format!("directories-test-diff-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        crate::tree! {
+            "same.txt" => "Hello",
+            "changed.txt" => "Old",
+            "any.txt" => "Whatever",
+            "flipped",
+            "nested/" { "deep.txt" => "Deep" },
+            "extra.txt",
+            "extra/",
+        }
+        .create(&root)
+        .unwrap();
+
+        // Identical trees don't differ, and files without contents match any contents
+        let expected: Tree = crate::tree! {
+            "same.txt" => "Hello",
+            "changed.txt" => "Old",
+            "any.txt",
+            "flipped",
+            "nested/" { "deep.txt" => "Deep" },
+            "extra.txt",
+            "extra/",
+        };
+        assert_eq!(expected.diff(&root).unwrap(), Vec::<String>::new());
+
+        // Otherwise, every difference is reported relative to the root
+        let expected: Tree = crate::tree! {
+            "same.txt" => "Hello",
+            "changed.txt" => "New",
+            "any.txt",
+            "flipped/",
+            "nested/" { "deep.txt" => "Deep", "missing.txt" },
+            "missing/",
+        };
+        let mut diffs: Vec<String> = expected.diff(&root).unwrap();
+        diffs.sort();
+        assert_eq!(diffs, vec![
+            "'changed.txt' has different contents".to_string(),
+            "'flipped' is a file, expected a directory".into(),
+            "Missing directory 'missing'".into(),
+            "Missing file 'nested/missing.txt'".into(),
+            "Unexpected directory 'extra'".into(),
+            "Unexpected file 'extra.txt'".into(),
+        ]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}